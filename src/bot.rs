@@ -0,0 +1,145 @@
+use bevy::{
+    ecs::resource::Resource,
+    platform::collections::{HashMap, HashSet},
+};
+use petgraph::graph::NodeIndex;
+
+use crate::components::{NodeKind, Owner};
+
+#[derive(Clone, Copy, Debug)]
+pub struct NodeView {
+    pub index: NodeIndex,
+    pub owner: Owner,
+    pub kind: NodeKind,
+    pub hp: f32,
+    pub max_hp: f32,
+}
+
+#[derive(Default)]
+pub struct BoardSnapshot {
+    pub nodes: HashMap<NodeIndex, NodeView>,
+    pub neighbors: HashMap<NodeIndex, Vec<NodeIndex>>,
+    pub flows: HashMap<NodeIndex, HashSet<NodeIndex>>,
+}
+
+impl BoardSnapshot {
+    pub fn owned_by(&self, owner: Owner) -> impl Iterator<Item = &NodeView> {
+        self.nodes.values().filter(move |node| node.owner == owner)
+    }
+
+    pub fn neighbors(&self, idx: NodeIndex) -> impl Iterator<Item = &NodeView> {
+        self.neighbors
+            .get(&idx)
+            .into_iter()
+            .flatten()
+            .filter_map(|neighbor| self.nodes.get(neighbor))
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum FlowCommand {
+    Add { from: NodeIndex, to: NodeIndex },
+    Remove { from: NodeIndex, to: NodeIndex },
+    ClearAll,
+}
+
+pub trait PlayerController: Send + Sync {
+    fn decide(&mut self, snapshot: &BoardSnapshot) -> Vec<FlowCommand>;
+}
+
+#[derive(Resource)]
+pub struct PlayerBot(pub Box<dyn PlayerController>);
+
+impl PlayerBot {
+    pub fn new(controller: impl PlayerController + 'static) -> Self {
+        Self(Box::new(controller))
+    }
+}
+
+#[derive(Default)]
+pub struct GreedyBot {
+    placed: HashMap<NodeIndex, NodeIndex>,
+}
+
+impl PlayerController for GreedyBot {
+    fn decide(&mut self, snapshot: &BoardSnapshot) -> Vec<FlowCommand> {
+        let has_flow = |from: NodeIndex, to: NodeIndex| {
+            snapshot
+                .flows
+                .get(&from)
+                .is_some_and(|targets| targets.contains(&to))
+        };
+        self.placed.retain(|&from, &mut to| has_flow(from, to));
+
+        let mut desired = HashMap::new();
+        for node in snapshot.owned_by(Owner::PLAYER) {
+            let weakest = snapshot
+                .neighbors(node.index)
+                .filter(|neighbor| neighbor.owner != Owner::PLAYER)
+                .min_by(|a, b| a.hp.total_cmp(&b.hp));
+
+            if let Some(target) = weakest {
+                desired.insert(node.index, target.index);
+            }
+        }
+
+        let mut commands = Vec::new();
+        for (&from, &to) in &self.placed {
+            if desired.get(&from) != Some(&to) {
+                commands.push(FlowCommand::Remove { from, to });
+            }
+        }
+        self.placed.retain(|from, to| desired.get(from) == Some(to));
+
+        for (&from, &to) in &desired {
+            if !has_flow(from, to) {
+                commands.push(FlowCommand::Add { from, to });
+                self.placed.insert(from, to);
+            }
+        }
+
+        commands
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn view(index: usize, owner: Owner, hp: f32) -> NodeView {
+        NodeView {
+            index: NodeIndex::new(index),
+            owner,
+            kind: NodeKind::Normal,
+            hp,
+            max_hp: 100.0,
+        }
+    }
+
+    #[test]
+    fn greedy_bot_leaves_human_flows_alone() {
+        let (base, weak, strong) = (NodeIndex::new(0), NodeIndex::new(1), NodeIndex::new(2));
+        let mut snapshot = BoardSnapshot::default();
+        for node in [
+            view(0, Owner::PLAYER, 100.0),
+            view(1, Owner::Neutral, 10.0),
+            view(2, Owner::Neutral, 50.0),
+        ] {
+            snapshot.nodes.insert(node.index, node);
+        }
+        snapshot.neighbors.insert(base, vec![weak, strong]);
+        snapshot.flows.entry(base).or_default().insert(strong);
+
+        let mut bot = GreedyBot::default();
+        assert_eq!(
+            bot.decide(&snapshot),
+            vec![FlowCommand::Add {
+                from: base,
+                to: weak
+            }]
+        );
+
+        snapshot.flows.entry(base).or_default().insert(weak);
+        assert!(bot.decide(&snapshot).is_empty());
+    }
+}
@@ -1,19 +1,137 @@
-use bevy::{color::Color, ecs::component::Component, platform::collections::HashSet, time::Timer};
+use bevy::{
+    color::Color,
+    ecs::component::Component,
+    platform::collections::{HashMap, HashSet},
+    time::{Timer, TimerMode},
+};
 use petgraph::graph::NodeIndex;
+use serde::{Deserialize, Serialize};
 
-#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+use crate::resources::{GameConfig, Palette};
+
+pub const PLAYER_FACTION: u8 = 0;
+const NODE_RADIUS: f32 = 0.06;
+
+const CAPTURE_BUFF_MULTIPLIER: f32 = 2.0;
+const COLOR_TRANSITION_SECS: f32 = 0.3;
+const FIRE_FLASH_SECS: f32 = 0.12;
+
+const FACTION_COLORS: [Color; 4] = [
+    Color::srgb(0.0, 4.0, 5.0),
+    Color::srgb(5.0, 1.0, 1.0),
+    Color::srgb(4.0, 3.0, 0.0),
+    Color::srgb(3.0, 0.5, 5.0),
+];
+
+const FACTION_PACKET_COLORS: [Color; 4] = [
+    Color::srgb(0.5, 0.5, 1.0),
+    Color::srgb(1.0, 0.5, 0.5),
+    Color::srgb(1.0, 0.8, 0.3),
+    Color::srgb(0.8, 0.4, 1.0),
+];
+
+const COLORBLIND_FACTION_COLORS: [Color; 4] = [
+    Color::srgb(0.0, 2.3, 4.5),
+    Color::srgb(4.5, 2.0, 0.0),
+    Color::srgb(4.0, 3.8, 1.0),
+    Color::srgb(4.0, 1.5, 3.0),
+];
+
+const COLORBLIND_PACKET_COLORS: [Color; 4] = [
+    Color::srgb(0.35, 0.7, 0.9),
+    Color::srgb(0.9, 0.6, 0.0),
+    Color::srgb(0.95, 0.9, 0.25),
+    Color::srgb(0.8, 0.47, 0.65),
+];
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Serialize, Deserialize)]
 pub enum Owner {
     Neutral,
-    Player,
-    Enemy,
+    Faction(u8),
 }
 
 impl Owner {
-    pub fn color(&self) -> Color {
+    pub const PLAYER: Owner = Owner::Faction(PLAYER_FACTION);
+
+    pub fn color(&self, palette: Palette) -> Color {
+        let colors = match palette {
+            Palette::Default => &FACTION_COLORS,
+            Palette::Colorblind => &COLORBLIND_FACTION_COLORS,
+        };
         match self {
             Owner::Neutral => Color::srgb(1.5, 1.5, 1.5),
-            Owner::Player => Color::srgb(0.0, 4.0, 5.0),
-            Owner::Enemy => Color::srgb(5.0, 1.0, 1.0),
+            Owner::Faction(id) => colors[*id as usize % colors.len()],
+        }
+    }
+
+    pub fn packet_color(&self, palette: Palette) -> Color {
+        let colors = match palette {
+            Palette::Default => &FACTION_PACKET_COLORS,
+            Palette::Colorblind => &COLORBLIND_PACKET_COLORS,
+        };
+        match self {
+            Owner::Neutral => Color::WHITE,
+            Owner::Faction(id) => colors[*id as usize % colors.len()],
+        }
+    }
+
+    pub fn name(&self) -> String {
+        match self {
+            Owner::Neutral => "Neutral".to_string(),
+            &Owner::PLAYER => "Player".to_string(),
+            Owner::Faction(id) => format!("AI {id}"),
+        }
+    }
+
+    pub fn is_ai(&self) -> bool {
+        matches!(self, Owner::Faction(id) if *id != PLAYER_FACTION)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
+pub enum NodeKind {
+    #[default]
+    Normal,
+    Fortress,
+    Spawner,
+}
+
+impl NodeKind {
+    pub fn hp_multiplier(&self) -> f32 {
+        match self {
+            NodeKind::Normal | NodeKind::Spawner => 1.0,
+            NodeKind::Fortress => 2.0,
+        }
+    }
+
+    pub fn radius(&self) -> f32 {
+        match self {
+            NodeKind::Normal => NODE_RADIUS,
+            NodeKind::Fortress => NODE_RADIUS * 1.25,
+            NodeKind::Spawner => NODE_RADIUS * 1.3,
+        }
+    }
+
+    pub fn damage_multiplier(&self) -> f32 {
+        match self {
+            NodeKind::Normal | NodeKind::Spawner => 1.0,
+            NodeKind::Fortress => 0.5,
+        }
+    }
+
+    pub fn packet_power_multiplier(&self) -> f32 {
+        match self {
+            NodeKind::Normal => 1.0,
+            NodeKind::Fortress => 1.5,
+            NodeKind::Spawner => 0.6,
+        }
+    }
+
+    pub fn packet_speed_multiplier(&self) -> f32 {
+        match self {
+            NodeKind::Normal => 1.0,
+            NodeKind::Fortress => 0.7,
+            NodeKind::Spawner => 1.4,
         }
     }
 }
@@ -21,10 +139,98 @@ impl Owner {
 #[derive(Component)]
 pub struct GameNode {
     pub index: NodeIndex,
+    pub kind: NodeKind,
     pub hp: f32,
+    pub max_hp: f32,
+    pub energy: f32,
     pub owner: Owner,
     pub targets: HashSet<NodeIndex>,
+    pub target_timers: HashMap<NodeIndex, Timer>,
+    pub last_hit: Timer,
+    pub buff_timer: Timer,
+    pub spawner_timer: Timer,
+    pub resistance_timer: Timer,
+    pub capture_cooldown: Timer,
+    pub color_transition: Option<(Owner, Timer)>,
+    pub fire_flash: Option<Timer>,
+    pub fire_phase: f32,
+}
+
+impl GameNode {
+    pub fn new(
+        index: NodeIndex,
+        kind: NodeKind,
+        owner: Owner,
+        hp: f32,
+        config: &GameConfig,
+    ) -> Self {
+        Self {
+            index,
+            kind,
+            hp,
+            max_hp: config.node_max_hp * kind.hp_multiplier(),
+            energy: config.max_energy,
+            owner,
+            targets: HashSet::new(),
+            target_timers: HashMap::new(),
+            last_hit: Timer::from_seconds(config.regen_delay, TimerMode::Once),
+            buff_timer: Timer::default(),
+            spawner_timer: Timer::from_seconds(config.spawner_interval, TimerMode::Repeating),
+            resistance_timer: Timer::default(),
+            capture_cooldown: Timer::default(),
+            color_transition: None,
+            fire_flash: None,
+            fire_phase: 0.0,
+        }
+    }
+
+    pub fn with_fire_phase(mut self, phase: f32) -> Self {
+        let delay = self.spawner_timer.duration().mul_f32(phase);
+        self.spawner_timer.set_elapsed(delay);
+        self.fire_phase = phase;
+        self
+    }
+
+    pub fn visual_scale(&self) -> f32 {
+        0.8 + 0.4 * (self.hp / self.max_hp).clamp(0.0, 1.0)
+    }
+
+    pub fn begin_color_transition(&mut self) {
+        self.color_transition = Some((
+            self.owner,
+            Timer::from_seconds(COLOR_TRANSITION_SECS, TimerMode::Once),
+        ));
+    }
+
+    pub fn begin_fire_flash(&mut self) {
+        self.fire_flash = Some(Timer::from_seconds(FIRE_FLASH_SECS, TimerMode::Once));
+    }
+
+    pub fn firing_multiplier(&self) -> f32 {
+        if self.buff_timer.remaining_secs() > 0.0 {
+            CAPTURE_BUFF_MULTIPLIER
+        } else {
+            1.0
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct GameEdge;
+
+#[derive(Component)]
+pub struct Obstacle;
+
+#[derive(Component)]
+pub struct CaptureRing {
     pub timer: Timer,
+    pub from_color: Color,
+    pub to_color: Color,
+}
+
+#[derive(Component)]
+pub struct Trail {
+    pub life: Timer,
 }
 
 #[derive(Component)]
@@ -34,4 +240,65 @@ pub struct Packet {
     pub owner: Owner,
     pub progress: f32,
     pub edge_len: f32,
+    pub power: f32,
+    pub speed: f32,
+}
+
+impl Packet {
+    pub fn new(
+        from: NodeIndex,
+        to: NodeIndex,
+        owner: Owner,
+        kind: NodeKind,
+        edge_len: f32,
+        config: &GameConfig,
+    ) -> Self {
+        Self {
+            from,
+            to,
+            owner,
+            progress: 0.0,
+            edge_len,
+            power: config.packet_power * kind.packet_power_multiplier(),
+            speed: config.packet_speed * kind.packet_speed_multiplier(),
+        }
+    }
+
+    pub fn travel_speed(&self, config: &GameConfig) -> f32 {
+        self.speed * config.packet_speed_multiplier / self.edge_len
+    }
+}
+
+#[derive(Component)]
+pub struct GameOverUi;
+
+#[derive(Component)]
+pub struct PausedUi;
+
+#[derive(Component)]
+pub struct HpLabel {
+    pub node: NodeIndex,
+}
+
+#[derive(Component)]
+pub struct IndexLabel;
+
+#[derive(Component)]
+pub struct Tooltip;
+
+#[derive(Component)]
+pub struct DebugOverlay;
+
+#[derive(Component)]
+pub struct HelpUi;
+
+#[derive(Component)]
+pub struct HudClock;
+
+#[derive(Component)]
+pub struct HudSpeed;
+
+#[derive(Component)]
+pub struct HudCounter {
+    pub owner: Owner,
 }
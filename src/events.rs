@@ -0,0 +1,16 @@
+use bevy::ecs::message::Message;
+use petgraph::graph::NodeIndex;
+
+use crate::components::Owner;
+
+#[derive(Message, Clone, Copy)]
+pub struct CaptureEvent {
+    pub node: NodeIndex,
+    pub previous_owner: Owner,
+    pub new_owner: Owner,
+}
+
+#[derive(Message, Clone, Copy)]
+pub struct FireEvent {
+    pub node: NodeIndex,
+}
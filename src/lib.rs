@@ -0,0 +1,81 @@
+use bevy::{
+    app::{App, Startup},
+    time::{Fixed, Time},
+};
+
+use crate::{
+    events::{CaptureEvent, FireEvent},
+    resources::{
+        AiRng, AiStrategy, AiTimer, AllowEnemyRoutes, ControlGroups, Difficulty, EdgeLoad,
+        EventLog, FlowHistory, FlowMap, FogOfWar, GameConfig, GameState, GraphEntityMap,
+        InteractionState, MapSeed, MatchClock, NodeSnapshot, OwnershipHistory, PacketCounts,
+        PacketPool, Palette, Paused, RegenTimer, ReplayMode, Scenario, ShowFrontier, ShowHpLabels,
+        ShowIndexLabels, ShowTrails, VictoryMode,
+    },
+    systems::map::setup_game,
+};
+
+pub mod bot;
+pub mod components;
+pub mod events;
+pub mod resources;
+pub mod sim;
+pub mod systems;
+
+pub const PACKET_SPEED: f32 = 1.0;
+pub const NODE_MAX_HP: f32 = 100.0;
+pub const PACKET_POWER: f32 = 1.0;
+pub const SPAWN_INTERVAL: f32 = 0.1;
+pub const FIXED_TIMESTEP: f32 = 1.0 / 64.0;
+
+#[derive(Default)]
+pub struct GameSettings {
+    pub difficulty: Difficulty,
+    pub config: GameConfig,
+    pub victory_mode: VictoryMode,
+    pub map_seed: MapSeed,
+    pub scenario: Option<Scenario>,
+}
+
+pub fn add_game_resources(app: &mut App, settings: GameSettings) {
+    let difficulty = settings.difficulty;
+
+    app.insert_resource(Time::<Fixed>::from_seconds(FIXED_TIMESTEP as f64))
+        .add_message::<CaptureEvent>()
+        .add_message::<FireEvent>()
+        .init_resource::<InteractionState>()
+        .init_resource::<ControlGroups>()
+        .init_resource::<GraphEntityMap>()
+        .init_resource::<NodeSnapshot>()
+        .insert_resource(FlowMap::new(settings.config.max_flows_per_node))
+        .init_resource::<FlowHistory>()
+        .insert_resource(difficulty)
+        .insert_resource(AiTimer::new(difficulty))
+        .init_resource::<AiStrategy>()
+        .init_resource::<GameState>()
+        .insert_resource(settings.victory_mode)
+        .init_resource::<MatchClock>()
+        .init_resource::<OwnershipHistory>()
+        .insert_resource(settings.config)
+        .init_resource::<PacketPool>()
+        .init_resource::<EdgeLoad>()
+        .init_resource::<PacketCounts>()
+        .init_resource::<Paused>()
+        .init_resource::<RegenTimer>()
+        .init_resource::<ShowHpLabels>()
+        .init_resource::<ShowIndexLabels>()
+        .init_resource::<ShowFrontier>()
+        .init_resource::<Palette>()
+        .init_resource::<ShowTrails>()
+        .init_resource::<AllowEnemyRoutes>()
+        .init_resource::<FogOfWar>()
+        .init_resource::<EventLog>()
+        .init_resource::<ReplayMode>()
+        .insert_resource(AiRng::new(&settings.map_seed))
+        .insert_resource(settings.map_seed)
+        .add_systems(Startup, setup_game);
+
+    if let Some(scenario) = settings.scenario {
+        app.insert_resource(scenario);
+    }
+}
@@ -1,58 +1,281 @@
 use bevy::{
-    camera::ScalingMode, core_pipeline::tonemapping::Tonemapping, platform::collections::HashSet,
-    post_process::bloom::Bloom, prelude::*,
+    camera::ScalingMode, core_pipeline::tonemapping::Tonemapping,
+    diagnostic::FrameTimeDiagnosticsPlugin, post_process::bloom::Bloom, prelude::*,
 };
-use petgraph::graph::NodeIndex;
-
-use crate::{
-    components::{GameNode, Owner},
-    resources::{AiTimer, ComputerGraph, FlowMap, GraphEntityMap, InteractionState},
+use virus_wars::{
+    GameSettings, add_game_resources,
+    bot::{GreedyBot, PlayerBot},
+    resources::{
+        Difficulty, GameConfig, KeyBindings, MapSeed, SandboxMode, Scenario, ShowDebugOverlay,
+        ShowEdgeLoad, ShowHelp, ShowOwnershipChart, TimeScale, VictoryMode,
+    },
+    sim::{add_headless_plugins, simulation_systems},
     systems::{
         ai::ai_behavior,
-        interaction::handle_interaction,
-        packet::{move_packets, spawn_packets},
-        visual::{draw_packet_trails, update_visuals},
+        bot::drive_player_bot,
+        camera::{camera_control, fit_camera_to_graph},
+        chart::{draw_ownership_chart, sample_ownership, toggle_ownership_chart},
+        debug::{toggle_debug_overlay, update_debug_overlay},
+        fog::toggle_fog,
+        fx::{animate_capture_rings, fade_trails, play_capture_fx, play_fire_fx, toggle_trails},
+        headless::report_headless_result,
+        help::{show_help_ui, toggle_help},
+        hud::{setup_hud, update_hud},
+        interaction::{handle_control_groups, handle_interaction},
+        labels::{toggle_hp_labels, toggle_index_labels, update_hp_labels, update_index_labels},
+        map::{load_map, restart_game, save_map},
+        minimap::draw_minimap,
+        packet::{
+            interpolate_packets, move_packets, resolve_packet_collisions, spawn_packets,
+            sync_packet_mesh,
+        },
+        pause::{change_time_scale, show_paused_ui, toggle_pause},
+        regen::{neutral_resistance, passive_regen, regen_energy, regen_neutral_nodes},
+        replay::{not_replaying, record_events, replay_events, start_replay},
+        sandbox::{cycle_node_owner, toggle_sandbox},
+        snapshot::update_node_snapshot,
+        tooltip::{setup_tooltip, update_tooltip},
+        victory::{check_victory, show_game_over_ui},
+        visual::{
+            cycle_palette, draw_flow_direction, draw_packet_trails, draw_selection_box,
+            toggle_edge_load, toggle_frontier, update_edge_widths, update_visuals,
+        },
     },
 };
 
-mod components;
-mod resources;
-mod systems;
-
-const PACKET_SPEED: f32 = 1.0;
-const NODE_MAX_HP: f32 = 100.0;
-const PACKET_POWER: f32 = 1.0;
-const SPAWN_INTERVAL: f32 = 0.1;
+#[cfg(feature = "audio")]
+use virus_wars::systems::audio::{
+    AudioState, play_capture_sounds, setup_audio, start_music, toggle_mute,
+};
 
 fn main() {
-    App::new()
-        .add_plugins(DefaultPlugins)
-        .init_resource::<InteractionState>()
-        .init_resource::<GraphEntityMap>()
-        .init_resource::<FlowMap>()
-        .init_resource::<AiTimer>()
-        .add_systems(Startup, setup_game)
+    let scenario = parse_scenario();
+    let victory_mode = scenario
+        .as_ref()
+        .and_then(|scenario| scenario.victory)
+        .unwrap_or_else(parse_victory_mode);
+
+    let mut app = App::new();
+    add_game_resources(
+        &mut app,
+        GameSettings {
+            difficulty: parse_difficulty(),
+            config: GameConfig {
+                faction_count: parse_faction_count(),
+                hostile_neutrals: std::env::args().any(|arg| arg == "--hostile-neutrals"),
+                packet_radius: parse_number("--packet-radius").unwrap_or(0.015),
+                packet_speed_multiplier: parse_number("--packet-speed").unwrap_or(1.0),
+                ..GameConfig::default()
+            },
+            victory_mode,
+            map_seed: MapSeed(parse_seed()),
+            scenario,
+        },
+    );
+
+    if std::env::args().any(|arg| arg == "--bot") {
+        app.insert_resource(PlayerBot::new(GreedyBot::default()));
+    }
+
+    if std::env::args().any(|arg| arg == "--headless") {
+        add_headless_systems(&mut app);
+    } else {
+        add_windowed_systems(&mut app);
+    }
+
+    app.run();
+}
+
+fn add_headless_systems(app: &mut App) {
+    add_headless_plugins(app);
+    app.insert_resource(PlayerBot::new(GreedyBot::default()))
+        .add_systems(
+            Update,
+            (simulation_systems(), report_headless_result).chain(),
+        );
+}
+
+fn add_windowed_systems(app: &mut App) {
+    app.add_plugins((DefaultPlugins, FrameTimeDiagnosticsPlugin::default()))
+        .init_resource::<ShowDebugOverlay>()
+        .init_resource::<SandboxMode>()
+        .init_resource::<TimeScale>()
+        .init_resource::<KeyBindings>()
+        .init_resource::<ShowHelp>()
+        .init_resource::<ShowEdgeLoad>()
+        .init_resource::<ShowOwnershipChart>()
+        .add_systems(Startup, (setup_camera, setup_hud, setup_tooltip))
         .add_systems(
             Update,
             (
-                handle_interaction,
-                ai_behavior,
-                spawn_packets,
+                restart_game,
+                start_replay,
+                save_map,
+                load_map,
+                toggle_pause,
+                change_time_scale,
+                (
+                    toggle_hp_labels,
+                    toggle_index_labels,
+                    toggle_debug_overlay,
+                    toggle_help,
+                    toggle_sandbox,
+                    toggle_fog,
+                    toggle_frontier,
+                    toggle_trails,
+                    toggle_edge_load,
+                    toggle_ownership_chart,
+                    cycle_palette,
+                ),
+                fit_camera_to_graph,
+                camera_control,
+                handle_control_groups.run_if(not_replaying),
+                handle_interaction.run_if(not_replaying),
+                cycle_node_owner.run_if(not_replaying),
+            )
+                .chain(),
+        )
+        .add_systems(
+            FixedUpdate,
+            (
+                update_node_snapshot,
+                ai_behavior.run_if(not_replaying),
+                drive_player_bot
+                    .run_if(not_replaying)
+                    .run_if(resource_exists::<PlayerBot>),
+                regen_neutral_nodes.run_if(not_replaying),
+                passive_regen.run_if(not_replaying),
+                neutral_resistance.run_if(not_replaying),
+                regen_energy.run_if(not_replaying),
+                spawn_packets.run_if(not_replaying),
+                replay_events,
                 move_packets,
-                update_visuals,
-                draw_packet_trails,
+                resolve_packet_collisions,
+                record_events,
+                sample_ownership,
+                check_victory,
             )
                 .chain(),
         )
-        .run();
+        .add_systems(
+            Update,
+            (
+                sync_packet_mesh,
+                interpolate_packets,
+                show_game_over_ui,
+                show_paused_ui,
+                show_help_ui,
+                update_hud,
+                update_tooltip,
+                update_visuals,
+                update_edge_widths,
+                update_hp_labels,
+                update_index_labels,
+                update_debug_overlay,
+                (play_capture_fx, play_fire_fx),
+                animate_capture_rings,
+                fade_trails,
+                draw_flow_direction,
+                draw_packet_trails,
+                draw_selection_box,
+                draw_minimap,
+                draw_ownership_chart,
+            )
+                .chain()
+                .after(handle_interaction),
+        );
+
+    #[cfg(feature = "gizmo_render")]
+    app.add_systems(
+        Update,
+        virus_wars::systems::gizmo_render::draw_graph_gizmos.after(update_visuals),
+    );
+
+    #[cfg(feature = "audio")]
+    app.init_resource::<AudioState>()
+        .add_systems(Startup, setup_audio)
+        .add_systems(Update, (start_music, toggle_mute, play_capture_sounds));
+}
+
+fn parse_seed() -> Option<u64> {
+    let args: Vec<String> = std::env::args().collect();
+    args.windows(2)
+        .find(|pair| pair[0] == "--seed")
+        .and_then(|pair| pair[1].parse().ok())
+}
+
+fn parse_faction_count() -> u8 {
+    let args: Vec<String> = std::env::args().collect();
+    if args.iter().any(|arg| arg == "--ffa") {
+        return 3;
+    }
+    args.windows(2)
+        .find(|pair| pair[0] == "--factions")
+        .and_then(|pair| pair[1].parse().ok())
+        .map_or(2, |count: u8| count.max(2))
+}
+
+fn parse_scenario() -> Option<Scenario> {
+    let args: Vec<String> = std::env::args().collect();
+    let path = args
+        .windows(2)
+        .find(|pair| pair[0] == "--scenario")
+        .map(|pair| pair[1].clone())?;
+
+    let json = match std::fs::read_to_string(&path) {
+        Ok(json) => json,
+        Err(err) => {
+            println!("Failed to read scenario {path}: {err}");
+            return None;
+        }
+    };
+    match Scenario::from_json(&json) {
+        Ok(scenario) => Some(scenario),
+        Err(err) => {
+            println!("Failed to load scenario {path}: {err}");
+            None
+        }
+    }
 }
 
-fn setup_game(
-    mut commands: Commands,
-    mut meshes: ResMut<Assets<Mesh>>,
-    mut materials: ResMut<Assets<ColorMaterial>>,
-    mut entity_map: ResMut<GraphEntityMap>,
-) {
+fn parse_victory_mode() -> VictoryMode {
+    let args: Vec<String> = std::env::args().collect();
+    let value = |flag: &str| {
+        args.windows(2)
+            .find(|pair| pair[0] == flag)
+            .map(|pair| pair[1].clone())
+    };
+    let number = |flag: &str| value(flag).and_then(|v| v.parse().ok());
+
+    value("--victory")
+        .and_then(|name| {
+            VictoryMode::parse(
+                &name,
+                number("--majority-share"),
+                number("--majority-hold"),
+                number("--time-limit"),
+            )
+        })
+        .unwrap_or_default()
+}
+
+fn parse_number(flag: &str) -> Option<f32> {
+    let args: Vec<String> = std::env::args().collect();
+    args.windows(2)
+        .find(|pair| pair[0] == flag)
+        .and_then(|pair| pair[1].parse().ok())
+        .filter(|value: &f32| *value > 0.0)
+}
+
+fn parse_difficulty() -> Difficulty {
+    let args: Vec<String> = std::env::args().collect();
+    args.windows(2)
+        .find(|pair| pair[0] == "--difficulty")
+        .and_then(|pair| Difficulty::parse(&pair[1]))
+        .unwrap_or_default()
+}
+
+fn setup_camera(mut commands: Commands) {
     commands.spawn((
         Camera2d,
         Projection::Orthographic(OrthographicProjection {
@@ -64,72 +287,4 @@ fn setup_game(
         Tonemapping::TonyMcMapface,
         Bloom::default(),
     ));
-
-    let computer_graph = ComputerGraph::random();
-    let graph = &computer_graph.0;
-
-    let player_start_idx = NodeIndex::new(0);
-    let enemy_start_idx = NodeIndex::new(graph.node_count() - 1);
-
-    let mesh_circle = meshes.add(Circle::new(0.06));
-    let mesh_edge = meshes.add(Rectangle::new(1.0, 0.02));
-
-    for node_idx in graph.node_indices() {
-        let node_data = graph[node_idx];
-
-        let (owner, hp) = if node_idx == player_start_idx {
-            (Owner::Player, 100.0)
-        } else if node_idx == enemy_start_idx {
-            (Owner::Enemy, 100.0)
-        } else {
-            (Owner::Neutral, 50.0)
-        };
-
-        let color = owner.color();
-        let material = materials.add(ColorMaterial::from(color));
-
-        let entity = commands
-            .spawn((
-                Mesh2d(mesh_circle.clone()),
-                MeshMaterial2d(material),
-                Transform::from_xyz(node_data.position.x, node_data.position.y, 1.0),
-                GameNode {
-                    index: node_idx,
-                    hp,
-                    owner,
-                    targets: HashSet::new(),
-                    timer: Timer::from_seconds(SPAWN_INTERVAL, TimerMode::Repeating),
-                },
-            ))
-            .id();
-
-        entity_map.nodes.insert(node_idx, entity);
-    }
-
-    let edge_color = materials.add(Color::srgb(0.2, 0.2, 0.2));
-
-    for edge_idx in graph.edge_indices() {
-        let (u, v) = graph.edge_endpoints(edge_idx).unwrap();
-        let pos_a = graph[u].position;
-        let pos_b = graph[v].position;
-
-        let diff = pos_b - pos_a;
-        let len = diff.length();
-        let pos = (pos_a + pos_b) / 2.0;
-        let angle = diff.y.atan2(diff.x);
-
-        let entity = commands
-            .spawn((
-                Mesh2d(mesh_edge.clone()),
-                MeshMaterial2d(edge_color.clone()),
-                Transform::from_xyz(pos.x, pos.y, 0.0)
-                    .with_rotation(Quat::from_rotation_z(angle))
-                    .with_scale(Vec3::new(len, 1.0, 1.0)),
-            ))
-            .id();
-
-        entity_map.edges.insert(edge_idx, entity);
-    }
-
-    commands.insert_resource(computer_graph);
 }
@@ -1,21 +1,168 @@
 use bevy::{
+    asset::{Assets, Handle},
+    color::{Alpha, Color},
     ecs::{entity::Entity, resource::Resource},
-    math::Vec2,
+    input::{ButtonInput, keyboard::KeyCode, mouse::MouseButton},
+    math::{Rect, Vec2, primitives::Circle},
+    mesh::Mesh,
     platform::collections::{HashMap, HashSet},
+    sprite_render::{AlphaMode2d, ColorMaterial},
     time::{Timer, TimerMode},
 };
 use petgraph::{
     Graph, Undirected,
     graph::{EdgeIndex, NodeIndex},
 };
-use rand::Rng;
+use rand::{Rng, SeedableRng, rngs::StdRng};
+use serde::{Deserialize, Serialize};
+use std::{collections::VecDeque, fmt};
 
-#[derive(Resource)]
-pub struct ComputerGraph(pub Graph<ComputerNode, (), Undirected>);
+use crate::{
+    NODE_MAX_HP, PACKET_POWER, PACKET_SPEED, SPAWN_INTERVAL,
+    components::{NodeKind, Owner},
+};
+
+#[derive(Resource, Clone)]
+pub struct ComputerGraph {
+    pub graph: Graph<ComputerNode, Edge, Undirected>,
+    pub obstacles: Vec<Rect>,
+}
+
+#[derive(Clone)]
+pub struct MapParams {
+    pub node_count: usize,
+    pub min_dist: f32,
+    pub connect_dist: f32,
+    pub bounds: Rect,
+    pub fortress_count: usize,
+    pub spawner_count: usize,
+    pub obstacles: Vec<Rect>,
+}
+
+impl Default for MapParams {
+    fn default() -> Self {
+        Self {
+            node_count: 40,
+            min_dist: 0.18,
+            connect_dist: 0.42,
+            bounds: Rect::new(-0.8, -0.8, 0.8, 0.8),
+            fortress_count: 3,
+            spawner_count: 2,
+            obstacles: Vec::new(),
+        }
+    }
+}
 
 #[derive(Clone, Copy)]
 pub struct ComputerNode {
     pub position: Vec2,
+    pub kind: NodeKind,
+    pub start: Option<NodeStart>,
+}
+
+#[derive(Clone, Copy, Serialize, Deserialize)]
+pub struct NodeStart {
+    pub owner: Owner,
+    pub hp: f32,
+}
+
+#[derive(Serialize, Deserialize)]
+struct MapFile {
+    nodes: Vec<MapFileNode>,
+    edges: Vec<[usize; 2]>,
+    #[serde(default)]
+    obstacles: Vec<[f32; 4]>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct MapFileNode {
+    x: f32,
+    y: f32,
+    #[serde(default)]
+    kind: NodeKind,
+    #[serde(default, flatten)]
+    start: Option<NodeStart>,
+}
+
+#[derive(Deserialize)]
+struct ScenarioFile {
+    name: String,
+    #[serde(default)]
+    description: String,
+    #[serde(default)]
+    victory: Option<VictoryMode>,
+    #[serde(flatten)]
+    map: MapFile,
+}
+
+#[derive(Resource, Clone)]
+pub struct Scenario {
+    pub name: String,
+    pub description: String,
+    pub victory: Option<VictoryMode>,
+    pub graph: ComputerGraph,
+}
+
+impl Scenario {
+    pub fn from_json(json: &str) -> Result<Self, MapError> {
+        let file: ScenarioFile = serde_json::from_str(json)?;
+        Ok(Self {
+            name: file.name,
+            description: file.description,
+            victory: file.victory,
+            graph: ComputerGraph::from_map_file(file.map)?,
+        })
+    }
+}
+
+#[derive(Debug)]
+pub enum MapError {
+    Json(serde_json::Error),
+    InvalidEdge(usize, usize),
+}
+
+impl fmt::Display for MapError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MapError::Json(err) => write!(f, "invalid map JSON: {err}"),
+            MapError::InvalidEdge(a, b) => write!(f, "edge {a}-{b} references a missing node"),
+        }
+    }
+}
+
+impl From<serde_json::Error> for MapError {
+    fn from(err: serde_json::Error) -> Self {
+        MapError::Json(err)
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct Edge {
+    pub length: f32,
+}
+
+#[derive(Resource, Default)]
+pub struct NodePositions(pub HashMap<NodeIndex, Vec2>);
+
+impl NodePositions {
+    pub fn from_graph(graph: &ComputerGraph) -> Self {
+        Self(
+            graph
+                .graph
+                .node_indices()
+                .map(|idx| (idx, graph.graph[idx].position))
+                .collect(),
+        )
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct NodeSnapshot(pub HashMap<NodeIndex, (Owner, f32, f32)>);
+
+impl NodeSnapshot {
+    pub fn owner(&self, node: NodeIndex) -> Option<Owner> {
+        self.0.get(&node).map(|&(owner, ..)| owner)
+    }
 }
 
 #[derive(Resource, Default)]
@@ -26,63 +173,1277 @@ pub struct GraphEntityMap {
 
 #[derive(Resource, Default)]
 pub struct InteractionState {
-    pub selected_source: Option<NodeIndex>,
+    pub dragging_from: Option<NodeIndex>,
     pub hovered_node: Option<NodeIndex>,
     pub path: Vec<NodeIndex>,
+    pub path_query: Option<(NodeIndex, NodeIndex)>,
+    pub unreachable: bool,
+    pub hovered_flow: Option<(NodeIndex, NodeIndex)>,
+    pub hovered_edge: Option<EdgeIndex>,
+    pub selection_box: Option<(Vec2, Vec2)>,
+    pub selected_sources: Vec<NodeIndex>,
+}
+
+impl InteractionState {
+    pub fn refresh_path(
+        &mut self,
+        find_path: impl FnOnce(NodeIndex, NodeIndex) -> Option<Vec<NodeIndex>>,
+    ) {
+        let path_query = self.dragging_from.zip(self.hovered_node);
+        if path_query == self.path_query {
+            return;
+        }
+
+        self.path_query = path_query;
+        self.path = path_query
+            .and_then(|(source, target)| find_path(source, target))
+            .unwrap_or_default();
+        self.unreachable =
+            path_query.is_some_and(|(source, target)| source != target) && self.path.is_empty();
+    }
 }
 
 #[derive(Resource, Default)]
+pub struct ControlGroups(pub HashMap<u8, Vec<NodeIndex>>);
+
+#[derive(Resource, Clone)]
 pub struct FlowMap {
     pub flows: HashMap<NodeIndex, HashSet<NodeIndex>>,
+    pub routes: Vec<Vec<NodeIndex>>,
+    pub weights: HashMap<(NodeIndex, NodeIndex), f32>,
+    pub max_flows: usize,
+}
+
+impl Default for FlowMap {
+    fn default() -> Self {
+        Self::new(usize::MAX)
+    }
+}
+
+impl FlowMap {
+    const MIN_WEIGHT: f32 = 0.25;
+    const MAX_WEIGHT: f32 = 4.0;
+
+    pub fn new(max_flows: usize) -> Self {
+        Self {
+            flows: HashMap::new(),
+            routes: Vec::new(),
+            weights: HashMap::new(),
+            max_flows,
+        }
+    }
+
+    pub fn insert_flow(&mut self, from: NodeIndex, to: NodeIndex) {
+        let targets = self.flows.get(&from);
+        if targets.is_some_and(|targets| targets.contains(&to))
+            || targets.map_or(0, |targets| targets.len()) >= self.max_flows
+        {
+            return;
+        }
+        self.flows.entry(from).or_default().insert(to);
+    }
+
+    pub fn weight(&self, from: NodeIndex, to: NodeIndex) -> f32 {
+        self.weights.get(&(from, to)).copied().unwrap_or(1.0)
+    }
+
+    pub fn adjust_weight(&mut self, from: NodeIndex, to: NodeIndex, factor: f32) -> f32 {
+        let weight = (self.weight(from, to) * factor).clamp(Self::MIN_WEIGHT, Self::MAX_WEIGHT);
+        self.weights.insert((from, to), weight);
+        weight
+    }
+
+    pub fn add_route(&mut self, path: &[NodeIndex], is_owned: impl Fn(NodeIndex) -> bool) {
+        if path.len() < 2 {
+            return;
+        }
+
+        for window in path.windows(2) {
+            if is_owned(window[0]) {
+                self.insert_flow(window[0], window[1]);
+            }
+        }
+
+        if !self.routes.iter().any(|route| route == path) {
+            self.routes.push(path.to_vec());
+        }
+    }
+
+    pub fn saturated_hop(
+        &self,
+        path: &[NodeIndex],
+        is_owned: impl Fn(NodeIndex) -> bool,
+    ) -> Option<NodeIndex> {
+        path.windows(2)
+            .filter(|hop| is_owned(hop[0]))
+            .find(|hop| {
+                self.flows.get(&hop[0]).is_some_and(|targets| {
+                    !targets.contains(&hop[1]) && targets.len() >= self.max_flows
+                })
+            })
+            .map(|hop| hop[0])
+    }
+
+    pub fn remove_route(&mut self, path: &[NodeIndex]) {
+        for window in path.windows(2) {
+            let (current_node, next_node) = (window[0], window[1]);
+
+            self.weights.remove(&(current_node, next_node));
+            if let Some(targets) = self.flows.get_mut(&current_node) {
+                targets.remove(&next_node);
+                if targets.is_empty() {
+                    self.flows.remove(&current_node);
+                }
+            }
+
+            self.routes.retain(|route| {
+                !route
+                    .windows(2)
+                    .any(|hop| hop[0] == current_node && hop[1] == next_node)
+            });
+        }
+    }
+
+    pub fn clear_node(&mut self, node: NodeIndex) -> usize {
+        self.routes
+            .retain(|route| !route[..route.len() - 1].contains(&node));
+        self.weights.retain(|&(from, _), _| from != node);
+        self.flows.remove(&node).map_or(0, |targets| targets.len())
+    }
+
+    pub fn clear(&mut self) -> usize {
+        let removed = self.flows.values().map(|targets| targets.len()).sum();
+        self.flows.clear();
+        self.routes.clear();
+        self.weights.clear();
+        removed
+    }
+
+    pub fn advance(&mut self, captured: NodeIndex) {
+        let next_hops: Vec<NodeIndex> = self
+            .routes
+            .iter()
+            .flat_map(|route| route.windows(2))
+            .filter(|hop| hop[0] == captured)
+            .map(|hop| hop[1])
+            .collect();
+        for next in next_hops {
+            self.insert_flow(captured, next);
+        }
+    }
+
+    fn set_weight(&mut self, key: (NodeIndex, NodeIndex), weight: Option<f32>) {
+        match weight {
+            Some(weight) => self.weights.insert(key, weight),
+            None => self.weights.remove(&key),
+        };
+    }
+
+    fn apply_edit(&mut self, edit: &FlowEdit, is_owned: impl Fn(NodeIndex) -> bool) {
+        for route in &edit.removed {
+            self.remove_route(route);
+        }
+        for route in &edit.added {
+            self.add_route(route, &is_owned);
+        }
+        for &(key, _, after) in &edit.weights {
+            self.set_weight(key, after);
+        }
+    }
+
+    fn revert_edit(&mut self, edit: &FlowEdit, is_owned: impl Fn(NodeIndex) -> bool) {
+        for route in &edit.added {
+            self.remove_route(route);
+        }
+        for route in &edit.removed {
+            self.add_route(route, &is_owned);
+        }
+        for &(key, before, _) in &edit.weights {
+            self.set_weight(key, before);
+        }
+    }
+}
+
+pub struct FlowEdit {
+    added: Vec<Vec<NodeIndex>>,
+    removed: Vec<Vec<NodeIndex>>,
+    weights: Vec<((NodeIndex, NodeIndex), Option<f32>, Option<f32>)>,
+}
+
+impl FlowEdit {
+    pub fn between(before: &FlowMap, after: &FlowMap) -> Self {
+        let added = after
+            .routes
+            .iter()
+            .filter(|route| !before.routes.contains(route))
+            .cloned()
+            .collect();
+        let removed = before
+            .routes
+            .iter()
+            .filter(|route| !after.routes.contains(route))
+            .cloned()
+            .collect();
+        let keys: HashSet<(NodeIndex, NodeIndex)> = before
+            .weights
+            .keys()
+            .chain(after.weights.keys())
+            .copied()
+            .collect();
+        let weights = keys
+            .into_iter()
+            .map(|key| {
+                (
+                    key,
+                    before.weights.get(&key).copied(),
+                    after.weights.get(&key).copied(),
+                )
+            })
+            .filter(|&(_, before, after)| before != after)
+            .collect();
+
+        Self {
+            added,
+            removed,
+            weights,
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.weights.is_empty()
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct FlowHistory {
+    undo: Vec<FlowEdit>,
+    redo: Vec<FlowEdit>,
+}
+
+impl FlowHistory {
+    const MAX_STEPS: usize = 100;
+
+    pub fn record(&mut self, edit: FlowEdit) {
+        if edit.is_empty() {
+            return;
+        }
+        if self.undo.len() == Self::MAX_STEPS {
+            self.undo.remove(0);
+        }
+        self.undo.push(edit);
+        self.redo.clear();
+    }
+
+    pub fn undo(&mut self, flow_map: &mut FlowMap, is_owned: impl Fn(NodeIndex) -> bool) -> bool {
+        let Some(edit) = self.undo.pop() else {
+            return false;
+        };
+        flow_map.revert_edit(&edit, is_owned);
+        self.redo.push(edit);
+        true
+    }
+
+    pub fn redo(&mut self, flow_map: &mut FlowMap, is_owned: impl Fn(NodeIndex) -> bool) -> bool {
+        let Some(edit) = self.redo.pop() else {
+            return false;
+        };
+        flow_map.apply_edit(&edit, is_owned);
+        self.undo.push(edit);
+        true
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum GameEvent {
+    Capture {
+        time: f32,
+        node: NodeIndex,
+        owner: Owner,
+    },
+    FlowAdded {
+        time: f32,
+        from: NodeIndex,
+        to: NodeIndex,
+    },
+    FlowRemoved {
+        time: f32,
+        from: NodeIndex,
+        to: NodeIndex,
+    },
+    PacketSpawned {
+        time: f32,
+        from: NodeIndex,
+        to: NodeIndex,
+        owner: Owner,
+    },
+}
+
+impl GameEvent {
+    pub fn time(&self) -> f32 {
+        match *self {
+            GameEvent::Capture { time, .. }
+            | GameEvent::FlowAdded { time, .. }
+            | GameEvent::FlowRemoved { time, .. }
+            | GameEvent::PacketSpawned { time, .. } => time,
+        }
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct EventLog {
+    pub events: Vec<GameEvent>,
+    pub clock: f32,
+    pub known_owners: HashMap<NodeIndex, Owner>,
+    pub known_flows: HashSet<(NodeIndex, NodeIndex)>,
+}
+
+#[derive(Resource, Default)]
+pub struct ReplayMode {
+    pub active: bool,
+    pub events: Vec<GameEvent>,
+    pub cursor: usize,
+    pub clock: f32,
+}
+
+#[derive(Resource, Default)]
+pub struct AiStrategy {
+    pub focus: HashMap<Owner, (NodeIndex, f32)>,
 }
 
 #[derive(Resource)]
 pub struct AiTimer(pub Timer);
 
-impl Default for AiTimer {
+impl AiTimer {
+    pub fn new(difficulty: Difficulty) -> Self {
+        Self(Timer::from_seconds(
+            difficulty.ai_interval(),
+            TimerMode::Repeating,
+        ))
+    }
+}
+
+#[derive(Resource)]
+pub struct RegenTimer(pub Timer);
+
+impl Default for RegenTimer {
+    fn default() -> Self {
+        Self(Timer::from_seconds(0.5, TimerMode::Repeating))
+    }
+}
+
+#[derive(Resource)]
+pub struct OwnershipHistory {
+    pub timer: Timer,
+    pub samples: VecDeque<HashMap<Owner, usize>>,
+}
+
+impl OwnershipHistory {
+    pub const SAMPLE_INTERVAL: f32 = 1.0;
+    pub const MAX_SAMPLES: usize = 90;
+
+    pub fn record(&mut self, sample: HashMap<Owner, usize>) {
+        if self.samples.len() == Self::MAX_SAMPLES {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(sample);
+    }
+}
+
+impl Default for OwnershipHistory {
+    fn default() -> Self {
+        Self {
+            timer: Timer::from_seconds(Self::SAMPLE_INTERVAL, TimerMode::Repeating),
+            samples: VecDeque::new(),
+        }
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct ShowOwnershipChart(pub bool);
+
+#[derive(Resource, Default, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Difficulty {
+    Easy,
+    #[default]
+    Normal,
+    Hard,
+}
+
+impl Difficulty {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "easy" => Some(Difficulty::Easy),
+            "normal" => Some(Difficulty::Normal),
+            "hard" => Some(Difficulty::Hard),
+            _ => None,
+        }
+    }
+
+    pub fn ai_interval(&self) -> f32 {
+        match self {
+            Difficulty::Easy => 4.0,
+            Difficulty::Normal => 2.0,
+            Difficulty::Hard => 1.0,
+        }
+    }
+
+    pub fn retreat_hp(&self) -> f32 {
+        match self {
+            Difficulty::Easy => 50.0,
+            Difficulty::Normal => 30.0,
+            Difficulty::Hard => 0.0,
+        }
+    }
+}
+
+#[derive(Resource, Default, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum GameState {
+    #[default]
+    Playing,
+    PlayerWon,
+    PlayerLost,
+}
+
+#[derive(Resource, Default, Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum VictoryMode {
+    #[default]
+    Annihilation,
+    Majority {
+        share: f32,
+        hold_secs: f32,
+    },
+    Timed {
+        duration_secs: f32,
+    },
+}
+
+impl VictoryMode {
+    pub fn parse(
+        name: &str,
+        share: Option<f32>,
+        hold_secs: Option<f32>,
+        duration_secs: Option<f32>,
+    ) -> Option<Self> {
+        match name {
+            "annihilation" => Some(Self::Annihilation),
+            "majority" => Some(Self::Majority {
+                share: share.unwrap_or(0.6),
+                hold_secs: hold_secs.unwrap_or(15.0),
+            }),
+            "timed" => Some(Self::Timed {
+                duration_secs: duration_secs.unwrap_or(180.0),
+            }),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct MatchClock {
+    pub elapsed: f32,
+    pub majority: Option<(Owner, f32)>,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum CaptureHp {
+    Flat(f32),
+    Fraction(f32),
+}
+
+impl CaptureHp {
+    pub fn resolve(self, max_hp: f32) -> f32 {
+        match self {
+            CaptureHp::Flat(hp) => hp.min(max_hp),
+            CaptureHp::Fraction(ratio) => max_hp * ratio,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum FullHpFlow {
+    Keep,
+    Hold,
+    Remove,
+}
+
+#[derive(Resource)]
+pub struct GameConfig {
+    pub packet_speed: f32,
+    pub node_max_hp: f32,
+    pub packet_power: f32,
+    pub spawn_interval: f32,
+    pub neutral_hp_ratio: f32,
+    pub neutral_regen_rate: f32,
+    pub passive_regen_rate: f32,
+    pub regen_delay: f32,
+    pub forward_surplus_packets: bool,
+    pub max_energy: f32,
+    pub energy_regen_rate: f32,
+    pub packet_energy_cost: f32,
+    pub capture_buff_duration: f32,
+    pub spawner_interval: f32,
+    pub faction_count: u8,
+    pub hostile_neutrals: bool,
+    pub resistance_window: f32,
+    pub resistance_decay_rate: f32,
+    pub packet_radius: f32,
+    pub packet_speed_multiplier: f32,
+    pub max_flows_per_node: usize,
+    pub max_packets_per_edge: usize,
+    pub max_packets_per_owner: usize,
+    pub capture_hp: CaptureHp,
+    pub capture_cooldown: f32,
+    pub full_hp_flow: FullHpFlow,
+}
+
+impl Default for GameConfig {
     fn default() -> Self {
-        Self(Timer::from_seconds(2.0, TimerMode::Repeating))
+        Self {
+            packet_speed: PACKET_SPEED,
+            node_max_hp: NODE_MAX_HP,
+            packet_power: PACKET_POWER,
+            spawn_interval: SPAWN_INTERVAL,
+            neutral_hp_ratio: 0.5,
+            neutral_regen_rate: 1.0,
+            passive_regen_rate: 2.0,
+            regen_delay: 3.0,
+            forward_surplus_packets: false,
+            max_energy: 30.0,
+            energy_regen_rate: 8.0,
+            packet_energy_cost: 1.0,
+            capture_buff_duration: 2.0,
+            spawner_interval: 0.5,
+            faction_count: 2,
+            hostile_neutrals: false,
+            resistance_window: 20.0,
+            resistance_decay_rate: 0.5,
+            packet_radius: 0.015,
+            packet_speed_multiplier: 1.0,
+            max_flows_per_node: 3,
+            max_packets_per_edge: 100,
+            max_packets_per_owner: 5000,
+            capture_hp: CaptureHp::Flat(10.0),
+            capture_cooldown: 0.1,
+            full_hp_flow: FullHpFlow::Hold,
+        }
+    }
+}
+
+impl GameConfig {
+    pub fn packet_travel_speed(&self) -> f32 {
+        self.packet_speed * self.packet_speed_multiplier
+    }
+}
+
+#[derive(Resource)]
+pub struct PacketAssets {
+    pub mesh: Handle<Mesh>,
+    pub faction_materials: HashMap<Owner, Handle<ColorMaterial>>,
+    pub neutral_material: Handle<ColorMaterial>,
+    pub hidden_material: Handle<ColorMaterial>,
+    pub trail_materials: HashMap<Owner, Handle<ColorMaterial>>,
+}
+
+impl PacketAssets {
+    pub fn new(
+        meshes: &mut Assets<Mesh>,
+        materials: &mut Assets<ColorMaterial>,
+        config: &GameConfig,
+        palette: Palette,
+    ) -> Self {
+        Self {
+            mesh: meshes.add(Circle::new(config.packet_radius)),
+            faction_materials: (0..config.faction_count)
+                .map(Owner::Faction)
+                .map(|owner| (owner, materials.add(owner.packet_color(palette))))
+                .collect(),
+            neutral_material: materials.add(Owner::Neutral.packet_color(palette)),
+            hidden_material: materials.add(Color::srgb(0.3, 0.3, 0.3)),
+            trail_materials: (0..config.faction_count)
+                .map(Owner::Faction)
+                .chain([Owner::Neutral])
+                .map(|owner| {
+                    let material = ColorMaterial {
+                        color: owner.packet_color(palette).with_alpha(0.35),
+                        alpha_mode: AlphaMode2d::Blend,
+                        ..ColorMaterial::default()
+                    };
+                    (owner, materials.add(material))
+                })
+                .collect(),
+        }
+    }
+
+    pub fn material(&self, owner: Owner) -> Handle<ColorMaterial> {
+        self.faction_materials
+            .get(&owner)
+            .unwrap_or(&self.neutral_material)
+            .clone()
+    }
+
+    pub fn trail_material(&self, owner: Owner) -> Handle<ColorMaterial> {
+        self.trail_materials
+            .get(&owner)
+            .unwrap_or(&self.trail_materials[&Owner::Neutral])
+            .clone()
+    }
+
+    pub fn recolor(&self, materials: &mut Assets<ColorMaterial>, palette: Palette) {
+        let faction_materials = self
+            .faction_materials
+            .iter()
+            .chain([(&Owner::Neutral, &self.neutral_material)]);
+        for (owner, handle) in faction_materials {
+            if let Some(material) = materials.get_mut(handle) {
+                material.color = owner.packet_color(palette);
+            }
+        }
+        for (owner, handle) in &self.trail_materials {
+            if let Some(material) = materials.get_mut(handle) {
+                material.color = owner.packet_color(palette).with_alpha(0.35);
+            }
+        }
+    }
+}
+
+#[derive(Resource)]
+pub struct EdgeMaterials {
+    pub default: Handle<ColorMaterial>,
+    pub path: Handle<ColorMaterial>,
+    pub erase: Handle<ColorMaterial>,
+    pub frontier: Handle<ColorMaterial>,
+    pub hover: Handle<ColorMaterial>,
+    flows: HashMap<u32, Handle<ColorMaterial>>,
+}
+
+impl EdgeMaterials {
+    pub fn new(materials: &mut Assets<ColorMaterial>) -> Self {
+        Self {
+            default: materials.add(Color::srgb(0.2, 0.2, 0.2)),
+            path: materials.add(Color::srgb(10.0, 10.0, 0.0)),
+            erase: materials.add(Color::srgb(10.0, 0.0, 0.0)),
+            frontier: materials.add(Color::srgb(6.0, 2.0, 0.0)),
+            hover: materials.add(Color::srgb(2.0, 2.0, 2.0)),
+            flows: HashMap::new(),
+        }
+    }
+
+    pub fn recolor(&self, materials: &mut Assets<ColorMaterial>, palette: Palette) {
+        if let Some(material) = materials.get_mut(&self.path) {
+            material.color = palette.path_edge_color(false);
+        }
+        if let Some(material) = materials.get_mut(&self.erase) {
+            material.color = palette.path_edge_color(true);
+        }
+    }
+
+    pub fn flow(
+        &mut self,
+        materials: &mut Assets<ColorMaterial>,
+        brightness: f32,
+    ) -> Handle<ColorMaterial> {
+        let key = (brightness * 100.0).round() as u32;
+        self.flows
+            .entry(key)
+            .or_insert_with(|| materials.add(Color::srgb(0.0, 2.5 * brightness, 10.0 * brightness)))
+            .clone()
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct PacketPool(pub Vec<Entity>);
+
+#[derive(Resource, Default)]
+pub struct PacketCounts(pub HashMap<Owner, usize>);
+
+impl PacketCounts {
+    pub fn at_cap(&self, owner: Owner, cap: usize) -> bool {
+        self.0.get(&owner).is_some_and(|&count| count >= cap)
+    }
+
+    pub fn add(&mut self, owner: Owner) {
+        *self.0.entry(owner).or_default() += 1;
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct EdgeLoad(pub HashMap<EdgeIndex, usize>);
+
+impl EdgeLoad {
+    pub fn add(&mut self, graph: &ComputerGraph, from: NodeIndex, to: NodeIndex) {
+        if let Some(edge) = graph.graph.find_edge(from, to) {
+            *self.0.entry(edge).or_default() += 1;
+        }
+    }
+
+    pub fn try_reserve(
+        &mut self,
+        graph: &ComputerGraph,
+        from: NodeIndex,
+        to: NodeIndex,
+        capacity: usize,
+    ) -> bool {
+        let Some(edge) = graph.graph.find_edge(from, to) else {
+            return false;
+        };
+        let load = self.0.entry(edge).or_default();
+        if *load >= capacity {
+            return false;
+        }
+        *load += 1;
+        true
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct Paused(pub bool);
+
+#[derive(Resource)]
+pub struct TimeScale(pub f32);
+
+impl Default for TimeScale {
+    fn default() -> Self {
+        Self(1.0)
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct ShowHpLabels(pub bool);
+
+#[derive(Resource, Default)]
+pub struct ShowIndexLabels(pub bool);
+
+#[derive(Resource, Default)]
+pub struct ShowDebugOverlay(pub bool);
+
+#[derive(Resource, Default)]
+pub struct SandboxMode(pub bool);
+
+#[derive(Resource, Default)]
+pub struct ShowHelp(pub bool);
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Action {
+    Select,
+    RouteSelected,
+    ClearNodeFlows,
+    Erase,
+    BoxSelect,
+    ClearFlows,
+    Retreat,
+    Undo,
+    Redo,
+    ToggleEnemyRoutes,
+    PanUp,
+    PanDown,
+    PanLeft,
+    PanRight,
+    Pause,
+    SlowDown,
+    SpeedUp,
+    Restart,
+    StartReplay,
+    SaveMap,
+    LoadMap,
+    ToggleFog,
+    ToggleFrontier,
+    ToggleTrails,
+    ToggleEdgeLoad,
+    ToggleOwnershipChart,
+    ToggleHpLabels,
+    CyclePalette,
+    ToggleMute,
+    ToggleHelp,
+    ToggleDebugOverlay,
+    ToggleSandbox,
+    ToggleIndexLabels,
+    ControlGroup,
+}
+
+impl Action {
+    pub fn description(self) -> &'static str {
+        match self {
+            Action::Select => "Select a node, drag to draw a flow",
+            Action::RouteSelected => "Route selected nodes here",
+            Action::ClearNodeFlows => "Clear a node's flows",
+            Action::Erase => "Erase instead of draw (hold)",
+            Action::BoxSelect => "Box select, hold to store a control group",
+            Action::ClearFlows => "Clear all flows",
+            Action::Retreat => "Retreat selected nodes",
+            Action::Undo => "Undo flow edit",
+            Action::Redo => "Redo flow edit",
+            Action::ToggleEnemyRoutes => "Route through enemy nodes",
+            Action::PanUp => "Pan up",
+            Action::PanDown => "Pan down",
+            Action::PanLeft => "Pan left",
+            Action::PanRight => "Pan right",
+            Action::Pause => "Pause",
+            Action::SlowDown => "Slow down",
+            Action::SpeedUp => "Speed up",
+            Action::Restart => "Restart match",
+            Action::StartReplay => "Replay last match",
+            Action::SaveMap => "Save map",
+            Action::LoadMap => "Load map",
+            Action::ToggleFog => "Fog of war",
+            Action::ToggleFrontier => "Highlight frontier",
+            Action::ToggleTrails => "Packet trails",
+            Action::ToggleEdgeLoad => "Edge width by traffic",
+            Action::ToggleOwnershipChart => "Ownership history chart",
+            Action::ToggleHpLabels => "HP labels",
+            Action::CyclePalette => "Cycle palette",
+            Action::ToggleMute => "Mute",
+            Action::ToggleHelp => "This help",
+            Action::ToggleDebugOverlay => "FPS overlay",
+            Action::ToggleSandbox => "Sandbox mode",
+            Action::ToggleIndexLabels => "Node indices",
+            Action::ControlGroup => "Recall control group",
+        }
+    }
+}
+
+#[derive(Resource)]
+pub struct KeyBindings {
+    pub keys: Vec<(Action, Vec<KeyCode>)>,
+    pub mouse: Vec<(Action, MouseButton)>,
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        Self {
+            keys: vec![
+                (Action::Erase, vec![KeyCode::ShiftLeft, KeyCode::ShiftRight]),
+                (
+                    Action::BoxSelect,
+                    vec![KeyCode::ControlLeft, KeyCode::ControlRight],
+                ),
+                (Action::ClearFlows, vec![KeyCode::KeyC]),
+                (Action::Retreat, vec![KeyCode::KeyB]),
+                (Action::Undo, vec![KeyCode::KeyZ]),
+                (Action::Redo, vec![KeyCode::KeyY]),
+                (Action::ToggleEnemyRoutes, vec![KeyCode::KeyT]),
+                (Action::PanUp, vec![KeyCode::KeyW, KeyCode::ArrowUp]),
+                (Action::PanDown, vec![KeyCode::KeyS, KeyCode::ArrowDown]),
+                (Action::PanLeft, vec![KeyCode::KeyA, KeyCode::ArrowLeft]),
+                (Action::PanRight, vec![KeyCode::KeyD, KeyCode::ArrowRight]),
+                (Action::Pause, vec![KeyCode::Space]),
+                (Action::SlowDown, vec![KeyCode::BracketLeft]),
+                (Action::SpeedUp, vec![KeyCode::BracketRight]),
+                (Action::Restart, vec![KeyCode::KeyR]),
+                (Action::StartReplay, vec![KeyCode::KeyV]),
+                (Action::SaveMap, vec![KeyCode::F5]),
+                (Action::LoadMap, vec![KeyCode::F9]),
+                (Action::ToggleFog, vec![KeyCode::KeyF]),
+                (Action::ToggleFrontier, vec![KeyCode::KeyG]),
+                (Action::ToggleTrails, vec![KeyCode::KeyL]),
+                (Action::ToggleEdgeLoad, vec![KeyCode::KeyE]),
+                (Action::ToggleOwnershipChart, vec![KeyCode::KeyO]),
+                (Action::ToggleHpLabels, vec![KeyCode::KeyH]),
+                (Action::CyclePalette, vec![KeyCode::KeyP]),
+                (Action::ToggleMute, vec![KeyCode::KeyM]),
+                (Action::ToggleHelp, vec![KeyCode::Slash]),
+                (Action::ToggleDebugOverlay, vec![KeyCode::F1]),
+                (Action::ToggleSandbox, vec![KeyCode::F2]),
+                (Action::ToggleIndexLabels, vec![KeyCode::F3]),
+                (
+                    Action::ControlGroup,
+                    vec![
+                        KeyCode::Digit1,
+                        KeyCode::Digit2,
+                        KeyCode::Digit3,
+                        KeyCode::Digit4,
+                        KeyCode::Digit5,
+                        KeyCode::Digit6,
+                        KeyCode::Digit7,
+                        KeyCode::Digit8,
+                        KeyCode::Digit9,
+                    ],
+                ),
+            ],
+            mouse: vec![
+                (Action::Select, MouseButton::Left),
+                (Action::RouteSelected, MouseButton::Right),
+                (Action::ClearNodeFlows, MouseButton::Middle),
+            ],
+        }
+    }
+}
+
+impl KeyBindings {
+    pub fn keys(&self, action: Action) -> &[KeyCode] {
+        self.keys
+            .iter()
+            .find(|(bound, _)| *bound == action)
+            .map_or(&[], |(_, keys)| keys.as_slice())
+    }
+
+    pub fn pressed(&self, keyboard: &ButtonInput<KeyCode>, action: Action) -> bool {
+        keyboard.any_pressed(self.keys(action).iter().copied())
+    }
+
+    pub fn just_pressed(&self, keyboard: &ButtonInput<KeyCode>, action: Action) -> bool {
+        keyboard.any_just_pressed(self.keys(action).iter().copied())
+    }
+
+    pub fn just_pressed_slot(
+        &self,
+        keyboard: &ButtonInput<KeyCode>,
+        action: Action,
+    ) -> Option<usize> {
+        self.keys(action)
+            .iter()
+            .position(|&key| keyboard.just_pressed(key))
+    }
+
+    pub fn mouse_button(&self, action: Action) -> Option<MouseButton> {
+        self.mouse
+            .iter()
+            .find(|(bound, _)| *bound == action)
+            .map(|&(_, button)| button)
+    }
+
+    pub fn mouse_pressed(&self, mouse: &ButtonInput<MouseButton>, action: Action) -> bool {
+        self.mouse_button(action)
+            .is_some_and(|button| mouse.pressed(button))
+    }
+
+    pub fn mouse_just_pressed(&self, mouse: &ButtonInput<MouseButton>, action: Action) -> bool {
+        self.mouse_button(action)
+            .is_some_and(|button| mouse.just_pressed(button))
+    }
+
+    pub fn mouse_just_released(&self, mouse: &ButtonInput<MouseButton>, action: Action) -> bool {
+        self.mouse_button(action)
+            .is_some_and(|button| mouse.just_released(button))
+    }
+
+    pub fn key_label(&self, action: Action) -> String {
+        let mut names: Vec<String> = self.keys(action).iter().map(|&key| key_name(key)).collect();
+        names.dedup();
+        names.join(" / ")
+    }
+
+    pub fn help_lines(&self) -> Vec<String> {
+        let mouse = self
+            .mouse
+            .iter()
+            .map(|&(action, button)| (format!("{button:?} click"), action));
+        let keys = self
+            .keys
+            .iter()
+            .map(|(action, _)| (self.key_label(*action), *action));
+
+        mouse
+            .chain(keys)
+            .map(|(input, action)| format!("{input:<16} {}", action.description()))
+            .collect()
+    }
+}
+
+fn key_name(key: KeyCode) -> String {
+    match key {
+        KeyCode::BracketLeft => "[".to_string(),
+        KeyCode::BracketRight => "]".to_string(),
+        KeyCode::Slash => "?".to_string(),
+        KeyCode::ShiftLeft | KeyCode::ShiftRight => "Shift".to_string(),
+        KeyCode::ControlLeft | KeyCode::ControlRight => "Ctrl".to_string(),
+        _ => {
+            let name = format!("{key:?}");
+            ["Key", "Digit", "Arrow"]
+                .iter()
+                .find_map(|prefix| name.strip_prefix(prefix))
+                .map_or(name.clone(), str::to_string)
+        }
+    }
+}
+
+#[derive(Resource, Default, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Palette {
+    #[default]
+    Default,
+    Colorblind,
+}
+
+impl Palette {
+    pub fn next(self) -> Self {
+        match self {
+            Palette::Default => Palette::Colorblind,
+            Palette::Colorblind => Palette::Default,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Palette::Default => "default",
+            Palette::Colorblind => "colorblind",
+        }
+    }
+
+    pub fn selected_tint(self) -> Color {
+        match self {
+            Palette::Default => Color::srgb(1.0, 5.0, 1.0),
+            Palette::Colorblind => Color::srgb(5.0, 5.0, 5.0),
+        }
+    }
+
+    pub fn path_tint(self, erasing: bool) -> Color {
+        match (self, erasing) {
+            (Palette::Default, false) => Color::srgb(1.0, 1.0, 0.0),
+            (Palette::Default, true) => Color::srgb(1.0, 0.0, 0.0),
+            (Palette::Colorblind, false) => Color::srgb(1.0, 1.0, 1.0),
+            (Palette::Colorblind, true) => Color::srgb(1.0, 0.37, 0.0),
+        }
+    }
+
+    pub fn path_edge_color(self, erasing: bool) -> Color {
+        match (self, erasing) {
+            (Palette::Default, false) => Color::srgb(10.0, 10.0, 0.0),
+            (Palette::Default, true) => Color::srgb(10.0, 0.0, 0.0),
+            (Palette::Colorblind, false) => Color::srgb(10.0, 10.0, 10.0),
+            (Palette::Colorblind, true) => Color::srgb(10.0, 3.7, 0.0),
+        }
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct ShowFrontier(pub bool);
+
+#[derive(Resource)]
+pub struct ShowTrails(pub bool);
+
+impl Default for ShowTrails {
+    fn default() -> Self {
+        Self(true)
+    }
+}
+
+#[derive(Resource)]
+pub struct ShowEdgeLoad(pub bool);
+
+impl Default for ShowEdgeLoad {
+    fn default() -> Self {
+        Self(true)
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct AllowEnemyRoutes(pub bool);
+
+#[derive(Resource)]
+pub struct FogOfWar {
+    pub enabled: bool,
+    pub radius: usize,
+    pub visible: HashSet<NodeIndex>,
+}
+
+impl Default for FogOfWar {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            radius: 1,
+            visible: HashSet::new(),
+        }
+    }
+}
+
+impl FogOfWar {
+    pub const HIDDEN_COLOR: Color = Color::srgb(0.25, 0.25, 0.25);
+
+    pub fn is_visible(&self, node: NodeIndex) -> bool {
+        !self.enabled || self.visible.contains(&node)
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct MapSeed(pub Option<u64>);
+
+#[derive(Resource)]
+pub struct AiRng(pub StdRng);
+
+impl AiRng {
+    pub fn new(map_seed: &MapSeed) -> Self {
+        Self(StdRng::seed_from_u64(
+            map_seed.0.unwrap_or_else(|| rand::rng().random()),
+        ))
     }
 }
 
 impl ComputerGraph {
+    pub fn to_json(&self) -> String {
+        let map_file = MapFile {
+            nodes: self
+                .graph
+                .node_weights()
+                .map(|node| MapFileNode {
+                    x: node.position.x,
+                    y: node.position.y,
+                    kind: node.kind,
+                    start: node.start,
+                })
+                .collect(),
+            edges: self
+                .graph
+                .edge_indices()
+                .filter_map(|edge_idx| self.graph.edge_endpoints(edge_idx))
+                .map(|(u, v)| [u.index(), v.index()])
+                .collect(),
+            obstacles: self
+                .obstacles
+                .iter()
+                .map(|rect| [rect.min.x, rect.min.y, rect.max.x, rect.max.y])
+                .collect(),
+        };
+
+        serde_json::to_string_pretty(&map_file).expect("map file is always serializable")
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, MapError> {
+        Self::from_map_file(serde_json::from_str(json)?)
+    }
+
+    fn from_map_file(map_file: MapFile) -> Result<Self, MapError> {
+        let mut graph = Graph::new_undirected();
+
+        for node in &map_file.nodes {
+            graph.add_node(ComputerNode {
+                position: Vec2::new(node.x, node.y),
+                kind: node.kind,
+                start: node.start,
+            });
+        }
+
+        for &[a, b] in &map_file.edges {
+            if a >= map_file.nodes.len() || b >= map_file.nodes.len() {
+                return Err(MapError::InvalidEdge(a, b));
+            }
+
+            let (u, v) = (NodeIndex::new(a), NodeIndex::new(b));
+            let length = graph[u].position.distance(graph[v].position);
+            graph.add_edge(u, v, Edge { length });
+        }
+
+        let obstacles = map_file
+            .obstacles
+            .iter()
+            .map(|&[x0, y0, x1, y1]| Rect::new(x0, y0, x1, y1))
+            .collect();
+
+        Ok(Self { graph, obstacles })
+    }
+
     pub fn random() -> Self {
-        const NODE_COUNT: usize = 30;
+        Self::from_seed(rand::rng().random())
+    }
+
+    pub fn from_seed(seed: u64) -> Self {
+        const OBSTACLE_COUNT: usize = 2;
+        const LAYOUT_ATTEMPTS: usize = 16;
+
+        let params = MapParams::default();
+        let mut rng = StdRng::seed_from_u64(seed.wrapping_add(1));
+        for _ in 0..LAYOUT_ATTEMPTS {
+            let obstacles = (0..OBSTACLE_COUNT)
+                .map(|_| {
+                    let size = Vec2::new(rng.random_range(0.1..0.35), rng.random_range(0.1..0.35));
+                    let center = Vec2::new(
+                        rng.random_range(params.bounds.min.x..=params.bounds.max.x),
+                        rng.random_range(params.bounds.min.y..=params.bounds.max.y),
+                    );
+                    Rect::from_center_size(center, size)
+                })
+                .collect();
+            let candidate = MapParams {
+                obstacles,
+                ..params.clone()
+            };
+            if let Some(graph) = Self::try_generate(&candidate, seed) {
+                return graph;
+            }
+        }
+
+        Self::generate(&params, seed)
+    }
+
+    pub fn generate(params: &MapParams, seed: u64) -> Self {
+        Self::try_generate(params, seed).unwrap_or_else(|| {
+            println!("Obstacles split the map into unreachable islands, dropping them");
+            let open = MapParams {
+                obstacles: Vec::new(),
+                ..params.clone()
+            };
+            Self::try_generate(&open, seed).expect("a map without obstacles can always connect")
+        })
+    }
+
+    fn try_generate(params: &MapParams, seed: u64) -> Option<Self> {
         const ATTEMPTS: usize = 20;
-        const MIN_DIST: f32 = 0.2;
-        const CONNECT_DIST: f32 = 0.45;
+        const OBSTACLE_MARGIN: f32 = 0.05;
 
         let mut graph = Graph::new_undirected();
-        let mut rng = rand::rng();
+        let mut rng = StdRng::seed_from_u64(seed);
+        let bounds = params.bounds;
 
-        let mut positions: Vec<Vec2> = Vec::with_capacity(NODE_COUNT);
-        'outer: for _ in 0..(NODE_COUNT * ATTEMPTS) {
-            if positions.len() >= NODE_COUNT {
+        let mut positions: Vec<Vec2> = Vec::with_capacity(params.node_count);
+        'outer: for _ in 0..(params.node_count * ATTEMPTS) {
+            if positions.len() >= params.node_count {
                 break;
             }
-            let candidate = Vec2::new(rng.random_range(-0.8..0.8), rng.random_range(-0.8..0.8));
+            let candidate = Vec2::new(
+                rng.random_range(bounds.min.x..=bounds.max.x),
+                rng.random_range(bounds.min.y..=bounds.max.y),
+            );
 
             for pos in &positions {
-                if pos.distance(candidate) < MIN_DIST {
+                if pos.distance(candidate) < params.min_dist {
                     continue 'outer;
                 }
             }
+            if params
+                .obstacles
+                .iter()
+                .any(|rect| rect.inflate(OBSTACLE_MARGIN).contains(candidate))
+            {
+                continue;
+            }
             positions.push(candidate);
         }
 
+        if positions.len() < params.node_count {
+            println!(
+                "Placed {} of {} nodes; min_dist {} is too tight for the bounds",
+                positions.len(),
+                params.node_count,
+                params.min_dist
+            );
+        }
+
         let node_indices: Vec<NodeIndex> = positions
             .iter()
-            .map(|&pos| graph.add_node(ComputerNode { position: pos }))
+            .map(|&pos| {
+                graph.add_node(ComputerNode {
+                    position: pos,
+                    kind: NodeKind::Normal,
+                    start: None,
+                })
+            })
             .collect();
 
+        let blocked = |a: Vec2, b: Vec2| {
+            params
+                .obstacles
+                .iter()
+                .any(|&rect| segment_hits_rect(a, b, rect))
+        };
+
         for i in 0..node_indices.len() {
             for j in (i + 1)..node_indices.len() {
                 let idx_a = node_indices[i];
                 let idx_b = node_indices[j];
                 let pos_a = graph[idx_a].position;
                 let pos_b = graph[idx_b].position;
-                if pos_a.distance(pos_b) < CONNECT_DIST {
-                    graph.add_edge(idx_a, idx_b, ());
+                let length = pos_a.distance(pos_b);
+                if length < params.connect_dist && !blocked(pos_a, pos_b) {
+                    graph.add_edge(idx_a, idx_b, Edge { length });
                 }
             }
         }
@@ -109,25 +1470,229 @@ impl ComputerGraph {
 
             let mut min_dist = f32::MAX;
             let mut best_edge = None;
-            let island_a = &components[0];
-
-            for island_b in components.iter().skip(1) {
-                for &node_a in island_a {
-                    for &node_b in island_b {
-                        let dist = graph[node_a].position.distance(graph[node_b].position);
-                        if dist < min_dist {
-                            min_dist = dist;
-                            best_edge = Some((node_a, node_b));
+
+            for (i, island_a) in components.iter().enumerate() {
+                for island_b in components.iter().skip(i + 1) {
+                    for &node_a in island_a {
+                        for &node_b in island_b {
+                            let (pos_a, pos_b) = (graph[node_a].position, graph[node_b].position);
+                            let dist = pos_a.distance(pos_b);
+                            if dist < min_dist && !blocked(pos_a, pos_b) {
+                                min_dist = dist;
+                                best_edge = Some((node_a, node_b, dist));
+                            }
                         }
                     }
                 }
             }
-            if let Some((u, v)) = best_edge {
-                graph.add_edge(u, v, ());
-            } else {
-                break;
+
+            let (u, v, length) = best_edge?;
+            graph.add_edge(u, v, Edge { length });
+        }
+
+        let mut by_degree = node_indices.clone();
+        by_degree.sort_by_key(|&idx| std::cmp::Reverse(graph.neighbors(idx).count()));
+        for &idx in by_degree.iter().take(params.fortress_count) {
+            graph[idx].kind = NodeKind::Fortress;
+        }
+
+        let center = bounds.center();
+        let mut by_centrality: Vec<NodeIndex> = node_indices
+            .iter()
+            .copied()
+            .filter(|&idx| graph[idx].kind == NodeKind::Normal)
+            .collect();
+        by_centrality.sort_by(|&a, &b| {
+            let dist_a = graph[a].position.distance(center);
+            let dist_b = graph[b].position.distance(center);
+            dist_a.total_cmp(&dist_b)
+        });
+        for &idx in by_centrality.iter().take(params.spawner_count) {
+            graph[idx].kind = NodeKind::Spawner;
+        }
+
+        Some(Self {
+            graph,
+            obstacles: params.obstacles.clone(),
+        })
+    }
+}
+
+fn segment_hits_rect(from: Vec2, to: Vec2, rect: Rect) -> bool {
+    let delta = to - from;
+    let (mut t_min, mut t_max) = (0.0f32, 1.0f32);
+
+    for axis in 0..2 {
+        if delta[axis].abs() < f32::EPSILON {
+            if from[axis] < rect.min[axis] || from[axis] > rect.max[axis] {
+                return false;
+            }
+            continue;
+        }
+        let t0 = (rect.min[axis] - from[axis]) / delta[axis];
+        let t1 = (rect.max[axis] - from[axis]) / delta[axis];
+        t_min = t_min.max(t0.min(t1));
+        t_max = t_max.min(t0.max(t1));
+        if t_min > t_max {
+            return false;
+        }
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use petgraph::visit::Bfs;
+
+    const SEEDS: std::ops::Range<u64> = 0..64;
+
+    #[test]
+    fn generated_graphs_are_connected() {
+        for seed in SEEDS {
+            let graph = ComputerGraph::from_seed(seed).graph;
+            let Some(start) = graph.node_indices().next() else {
+                panic!("seed {seed} produced an empty graph");
+            };
+
+            let mut bfs = Bfs::new(&graph, start);
+            let mut reached = 0;
+            while bfs.next(&graph).is_some() {
+                reached += 1;
             }
+            assert_eq!(reached, graph.node_count(), "seed {seed} is disconnected");
         }
-        Self(graph)
+    }
+
+    #[test]
+    fn generated_nodes_respect_min_dist() {
+        let params = MapParams::default();
+        for seed in SEEDS {
+            let graph = ComputerGraph::from_seed(seed).graph;
+            for a in graph.node_indices() {
+                for b in graph.node_indices().filter(|&b| b > a) {
+                    let dist = graph[a].position.distance(graph[b].position);
+                    assert!(
+                        dist >= params.min_dist,
+                        "seed {seed}: nodes {} and {} are {dist} apart",
+                        a.index(),
+                        b.index()
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn generated_node_count_is_within_bounds() {
+        let params = MapParams::default();
+        for seed in SEEDS {
+            let graph = ComputerGraph::from_seed(seed).graph;
+            let count = graph.node_count();
+            assert!(
+                count >= params.node_count * 3 / 4 && count <= params.node_count,
+                "seed {seed} placed {count} nodes"
+            );
+            for idx in graph.node_indices() {
+                assert!(params.bounds.contains(graph[idx].position));
+            }
+        }
+    }
+
+    #[test]
+    fn generated_nodes_avoid_obstacles() {
+        for seed in SEEDS {
+            let ComputerGraph { graph, obstacles } = ComputerGraph::from_seed(seed);
+            assert!(!obstacles.is_empty());
+            for idx in graph.node_indices() {
+                let pos = graph[idx].position;
+                assert!(
+                    !obstacles.iter().any(|rect| rect.contains(pos)),
+                    "seed {seed}: node {} sits inside an obstacle",
+                    idx.index()
+                );
+            }
+        }
+    }
+
+    fn crossing_edge(map: &ComputerGraph) -> Option<(NodeIndex, NodeIndex)> {
+        map.graph
+            .edge_indices()
+            .filter_map(|edge| map.graph.edge_endpoints(edge))
+            .find(|&(u, v)| {
+                let (from, to) = (map.graph[u].position, map.graph[v].position);
+                map.obstacles
+                    .iter()
+                    .any(|&rect| segment_hits_rect(from, to, rect))
+            })
+    }
+
+    #[test]
+    fn generated_edges_avoid_obstacles() {
+        for seed in SEEDS {
+            let map = ComputerGraph::from_seed(seed);
+            assert_eq!(crossing_edge(&map), None, "seed {seed}");
+        }
+    }
+
+    #[test]
+    fn splitting_obstacle_never_yields_blocked_edges() {
+        let params = MapParams {
+            obstacles: vec![Rect::new(-0.05, -1.0, 0.05, 1.0)],
+            ..MapParams::default()
+        };
+        for seed in SEEDS {
+            let map = ComputerGraph::generate(&params, seed);
+            assert_eq!(crossing_edge(&map), None, "seed {seed}");
+        }
+    }
+
+    #[test]
+    fn drag_path_is_stable_without_input_change() {
+        let (source, target) = (NodeIndex::new(0), NodeIndex::new(1));
+        let mut state = InteractionState {
+            dragging_from: Some(source),
+            hovered_node: Some(target),
+            ..InteractionState::default()
+        };
+        let mut searches = 0;
+
+        for _ in 0..2 {
+            state.refresh_path(|from, to| {
+                searches += 1;
+                Some(vec![from, to])
+            });
+            assert_eq!(state.path, vec![source, target]);
+        }
+        assert_eq!(searches, 1);
+
+        state.hovered_node = Some(NodeIndex::new(2));
+        state.refresh_path(|from, to| {
+            searches += 1;
+            Some(vec![from, to])
+        });
+        assert_eq!(state.path, vec![source, NodeIndex::new(2)]);
+        assert_eq!(searches, 2);
+    }
+
+    #[test]
+    fn flow_cap_holds_for_every_insertion_path() {
+        let source = NodeIndex::new(0);
+        let mut flow_map = FlowMap::new(2);
+        for target in 1..=3 {
+            flow_map.add_route(&[source, NodeIndex::new(target)], |_| false);
+        }
+
+        flow_map.advance(source);
+        assert_eq!(flow_map.flows[&source].len(), 2);
+
+        let before = flow_map.clone();
+        flow_map.clear_node(source);
+        let mut history = FlowHistory::default();
+        history.record(FlowEdit::between(&before, &flow_map));
+        flow_map.add_route(&[source, NodeIndex::new(3)], |_| true);
+        flow_map.add_route(&[source, NodeIndex::new(4)], |_| true);
+        assert!(history.undo(&mut flow_map, |_| true));
+        assert_eq!(flow_map.flows[&source].len(), 2);
     }
 }
@@ -0,0 +1,256 @@
+use bevy::{
+    MinimalPlugins,
+    app::{App, Update},
+    asset::Assets,
+    ecs::{
+        schedule::{IntoScheduleConfigs, ScheduleConfigs, common_conditions::resource_exists},
+        system::ScheduleSystem,
+        world::World,
+    },
+    mesh::Mesh,
+    platform::collections::HashMap,
+    sprite_render::ColorMaterial,
+    time::TimeUpdateStrategy,
+};
+use petgraph::graph::NodeIndex;
+use std::time::Duration;
+
+use crate::{
+    FIXED_TIMESTEP, GameSettings, add_game_resources,
+    bot::{PlayerBot, PlayerController},
+    components::{GameNode, Owner},
+    resources::{ComputerGraph, FlowMap, GameState, ShowTrails},
+    systems::{
+        ai::ai_behavior,
+        bot::drive_player_bot,
+        packet::{move_packets, resolve_packet_collisions, spawn_packets},
+        regen::{neutral_resistance, passive_regen, regen_energy, regen_neutral_nodes},
+        snapshot::update_node_snapshot,
+        victory::check_victory,
+    },
+};
+
+pub fn add_headless_plugins(app: &mut App) {
+    app.add_plugins(MinimalPlugins)
+        .init_resource::<Assets<Mesh>>()
+        .init_resource::<Assets<ColorMaterial>>()
+        .insert_resource(ShowTrails(false))
+        .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f32(
+            FIXED_TIMESTEP,
+        )));
+}
+
+pub fn simulation_systems() -> ScheduleConfigs<ScheduleSystem> {
+    (
+        update_node_snapshot,
+        ai_behavior,
+        drive_player_bot.run_if(resource_exists::<PlayerBot>),
+        regen_neutral_nodes,
+        passive_regen,
+        neutral_resistance,
+        regen_energy,
+        spawn_packets,
+        move_packets,
+        resolve_packet_collisions,
+        check_victory,
+    )
+        .chain()
+        .into_configs()
+}
+
+pub struct GameSim {
+    app: App,
+    accumulator: f32,
+    ticks: u64,
+}
+
+impl GameSim {
+    pub fn new(settings: GameSettings) -> Self {
+        let mut app = App::new();
+        add_headless_plugins(&mut app);
+        add_game_resources(&mut app, settings);
+        app.add_systems(Update, simulation_systems());
+        app.finish();
+        app.cleanup();
+        app.update();
+        Self {
+            app,
+            accumulator: 0.0,
+            ticks: 0,
+        }
+    }
+
+    pub fn step(&mut self, dt: f32) {
+        self.accumulator += dt;
+        let ticks = (self.accumulator / FIXED_TIMESTEP).floor() as u32;
+        self.accumulator -= ticks as f32 * FIXED_TIMESTEP;
+        for _ in 0..ticks {
+            self.app.update();
+        }
+        self.ticks += u64::from(ticks);
+    }
+
+    pub fn ticks(&self) -> u64 {
+        self.ticks
+    }
+
+    pub fn owner_counts(&mut self) -> HashMap<Owner, usize> {
+        let world = self.app.world_mut();
+        let mut counts = HashMap::new();
+        for node in world.query::<&GameNode>().iter(world) {
+            *counts.entry(node.owner).or_default() += 1;
+        }
+        counts
+    }
+
+    pub fn set_flow(&mut self, from: NodeIndex, to: NodeIndex) -> bool {
+        let world = self.app.world_mut();
+        if !world
+            .resource::<ComputerGraph>()
+            .graph
+            .contains_edge(from, to)
+        {
+            return false;
+        }
+
+        let player_nodes: Vec<NodeIndex> = world
+            .query::<&GameNode>()
+            .iter(world)
+            .filter(|node| node.owner == Owner::PLAYER)
+            .map(|node| node.index)
+            .collect();
+        let is_player_node = |idx: NodeIndex| player_nodes.contains(&idx);
+        let mut flow_map = world.resource_mut::<FlowMap>();
+
+        let path = [from, to];
+        if !is_player_node(from) || flow_map.saturated_hop(&path, is_player_node).is_some() {
+            return false;
+        }
+        flow_map.add_route(&path, is_player_node);
+        true
+    }
+
+    pub fn set_player_bot(&mut self, controller: impl PlayerController + 'static) {
+        self.app.insert_resource(PlayerBot::new(controller));
+    }
+
+    pub fn game_state(&self) -> GameState {
+        *self.app.world().resource::<GameState>()
+    }
+
+    pub fn world(&self) -> &World {
+        self.app.world()
+    }
+
+    pub fn world_mut(&mut self) -> &mut World {
+        self.app.world_mut()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        components::Packet,
+        resources::{KeyBindings, MapSeed},
+        systems::visual::update_visuals,
+    };
+    use bevy::input::{ButtonInput, keyboard::KeyCode};
+
+    fn player_node_and_neighbor(sim: &mut GameSim) -> (NodeIndex, NodeIndex) {
+        let world = sim.world_mut();
+        let player_node = world
+            .query::<&GameNode>()
+            .iter(world)
+            .find(|node| node.owner == Owner::PLAYER)
+            .map(|node| node.index)
+            .unwrap();
+        let neighbor = world
+            .resource::<ComputerGraph>()
+            .graph
+            .neighbors(player_node)
+            .next()
+            .unwrap();
+        (player_node, neighbor)
+    }
+
+    #[test]
+    fn seeded_sim_steps_without_losing_nodes() {
+        let mut sim = GameSim::new(GameSettings {
+            map_seed: MapSeed(Some(3)),
+            ..GameSettings::default()
+        });
+        let node_count = sim.world().resource::<ComputerGraph>().graph.node_count();
+
+        sim.step(5.0);
+
+        let counts = sim.owner_counts();
+        assert_eq!(counts.values().sum::<usize>(), node_count);
+        assert!(counts.get(&Owner::PLAYER).is_some_and(|&count| count > 0));
+        assert_eq!(sim.game_state(), GameState::Playing);
+    }
+
+    #[test]
+    fn small_steps_accumulate_into_whole_ticks() {
+        let mut coarse = GameSim::new(GameSettings {
+            map_seed: MapSeed(Some(3)),
+            ..GameSettings::default()
+        });
+        let mut fine = GameSim::new(GameSettings {
+            map_seed: MapSeed(Some(3)),
+            ..GameSettings::default()
+        });
+
+        coarse.step(0.64);
+        for _ in 0..64 {
+            fine.step(0.01);
+        }
+
+        assert_eq!(coarse.ticks(), (0.64 / FIXED_TIMESTEP) as u64);
+        assert_eq!(fine.ticks(), coarse.ticks());
+    }
+
+    #[test]
+    fn set_flow_requires_owned_adjacent_source() {
+        let mut sim = GameSim::new(GameSettings {
+            map_seed: MapSeed(Some(3)),
+            ..GameSettings::default()
+        });
+        let (player_node, neighbor) = player_node_and_neighbor(&mut sim);
+        let graph = &sim.world().resource::<ComputerGraph>().graph;
+        let stranger = graph
+            .node_indices()
+            .find(|&idx| idx != player_node && !graph.contains_edge(player_node, idx))
+            .unwrap();
+
+        assert!(sim.set_flow(player_node, neighbor));
+        assert!(!sim.set_flow(player_node, stranger));
+    }
+
+    #[test]
+    fn sustained_firing_keeps_material_count_constant() {
+        let mut sim = GameSim::new(GameSettings {
+            map_seed: MapSeed(Some(3)),
+            ..GameSettings::default()
+        });
+        sim.app
+            .init_resource::<ButtonInput<KeyCode>>()
+            .init_resource::<KeyBindings>()
+            .add_systems(Update, update_visuals.after(check_victory));
+        let (player_node, neighbor) = player_node_and_neighbor(&mut sim);
+        assert!(sim.set_flow(player_node, neighbor));
+
+        sim.step(1.0);
+        let material_count = sim.world().resource::<Assets<ColorMaterial>>().len();
+
+        for _ in 0..10 {
+            sim.step(1.0);
+            let world = sim.world_mut();
+            assert!(world.query::<&Packet>().iter(world).next().is_some());
+            assert_eq!(
+                world.resource::<Assets<ColorMaterial>>().len(),
+                material_count
+            );
+        }
+    }
+}
@@ -1,39 +1,134 @@
 use bevy::{
     ecs::system::{Query, Res, ResMut},
+    platform::collections::HashMap,
     time::Time,
 };
 use rand::seq::IndexedRandom;
 
 use crate::{
     components::{GameNode, Owner},
-    resources::{AiTimer, ComputerGraph},
+    resources::{
+        AiRng, AiStrategy, AiTimer, ComputerGraph, Difficulty, GameState, NodeSnapshot, Paused,
+    },
 };
 
+const PLAYER_TARGET_PENALTY: f32 = 25.0;
+const THREATENED_HP_RATIO: f32 = 0.4;
+const HEALTHY_HP_RATIO: f32 = 0.6;
+const FOCUS_MIN_ATTACKERS: usize = 2;
+const FOCUS_ATTACKER_WEIGHT: f32 = 20.0;
+
+fn capture_score(owner: Owner, hp: f32) -> f32 {
+    match owner {
+        Owner::PLAYER => hp + PLAYER_TARGET_PENALTY,
+        _ => hp,
+    }
+}
+
 pub fn ai_behavior(
     mut nodes_q: Query<&mut GameNode>,
     graph_res: Res<ComputerGraph>,
     time: Res<Time>,
     mut ai_timer: ResMut<AiTimer>,
+    mut strategy: ResMut<AiStrategy>,
+    mut rng: ResMut<AiRng>,
+    difficulty: Res<Difficulty>,
+    game_state: Res<GameState>,
+    paused: Res<Paused>,
+    snapshot: Res<NodeSnapshot>,
 ) {
+    if *game_state != GameState::Playing || paused.0 {
+        return;
+    }
+
     ai_timer.0.tick(time.delta());
     if !ai_timer.0.is_finished() {
         return;
     }
 
-    let mut rng = rand::rng();
+    let node_states = &snapshot.0;
 
-    for mut node in nodes_q.iter_mut() {
-        if node.owner == Owner::Enemy {
-            node.targets.clear();
+    strategy.focus.clear();
+    for (&candidate, &(owner, hp, _)) in node_states {
+        let mut attackers: HashMap<Owner, usize> = HashMap::new();
+        for neighbor_idx in graph_res.graph.neighbors(candidate) {
+            if let Some(&(neighbor_owner, ..)) = node_states.get(&neighbor_idx)
+                && neighbor_owner.is_ai()
+                && neighbor_owner != owner
+            {
+                *attackers.entry(neighbor_owner).or_default() += 1;
+            }
+        }
 
-            if node.hp < 30.0 {
+        for (faction, count) in attackers {
+            if count < FOCUS_MIN_ATTACKERS {
                 continue;
             }
+            let score = count as f32 * FOCUS_ATTACKER_WEIGHT - capture_score(owner, hp);
+            let best = strategy.focus.get(&faction).map(|&(_, best)| best);
+            if best.is_none_or(|best| score > best) {
+                strategy.focus.insert(faction, (candidate, score));
+            }
+        }
+    }
 
-            let neighbors: Vec<_> = graph_res.0.neighbors(node.index).collect();
+    for mut node in nodes_q.iter_mut() {
+        if !node.owner.is_ai() {
+            continue;
+        }
+
+        node.targets.clear();
+
+        if node.hp < difficulty.retreat_hp() {
+            continue;
+        }
+
+        let mut hostile = Vec::new();
+        let mut wounded = Vec::new();
+        let mut threatened = None;
+        for neighbor_idx in graph_res.graph.neighbors(node.index) {
+            if let Some(&(owner, hp, max_hp)) = node_states.get(&neighbor_idx) {
+                if owner != node.owner {
+                    hostile.push((neighbor_idx, capture_score(owner, hp)));
+                } else if hp < max_hp {
+                    wounded.push(neighbor_idx);
+                    if hp < max_hp * THREATENED_HP_RATIO
+                        && threatened.is_none_or(|(_, lowest)| hp < lowest)
+                    {
+                        threatened = Some((neighbor_idx, hp));
+                    }
+                }
+            }
+        }
 
-            if let Some(&target_idx) = neighbors.choose(&mut rng) {
-                node.targets.insert(target_idx);
+        if node.hp >= node.max_hp * HEALTHY_HP_RATIO
+            && let Some((target_idx, _)) = threatened
+        {
+            node.targets.insert(target_idx);
+            continue;
+        }
+
+        if let Some(&(focus_idx, _)) = strategy.focus.get(&node.owner)
+            && graph_res.graph.contains_edge(node.index, focus_idx)
+        {
+            node.targets.insert(focus_idx);
+            continue;
+        }
+
+        hostile.sort_by(|a, b| a.1.total_cmp(&b.1));
+        let best_targets = hostile.iter().map(|&(idx, _)| idx);
+
+        match *difficulty {
+            Difficulty::Easy => {
+                if let Some(&target_idx) = wounded.choose(&mut rng.0) {
+                    node.targets.insert(target_idx);
+                } else {
+                    node.targets.extend(best_targets.take(1));
+                }
+            }
+            Difficulty::Normal | Difficulty::Hard => {
+                node.targets.extend(best_targets);
+                node.targets.extend(wounded);
             }
         }
     }
@@ -0,0 +1,140 @@
+use bevy::{
+    asset::{AssetServer, Handle},
+    audio::{AudioPlayer, AudioSink, AudioSinkPlayback, AudioSource, PlaybackSettings},
+    ecs::{
+        component::Component,
+        message::MessageReader,
+        query::With,
+        resource::Resource,
+        system::{Commands, Query, Res, ResMut},
+    },
+    input::{ButtonInput, keyboard::KeyCode},
+    time::Time,
+};
+
+use crate::{
+    components::Owner,
+    events::CaptureEvent,
+    resources::{Action, KeyBindings},
+};
+
+const SOUND_DEBOUNCE: f32 = 0.15;
+
+#[derive(Component)]
+pub struct Music;
+
+#[derive(Resource)]
+pub struct SoundAssets {
+    music: Handle<AudioSource>,
+    capture: Handle<AudioSource>,
+    node_lost: Handle<AudioSource>,
+}
+
+#[derive(Resource)]
+pub struct AudioState {
+    pub muted: bool,
+    last_capture: f32,
+    last_node_lost: f32,
+}
+
+impl Default for AudioState {
+    fn default() -> Self {
+        Self {
+            muted: false,
+            last_capture: f32::NEG_INFINITY,
+            last_node_lost: f32::NEG_INFINITY,
+        }
+    }
+}
+
+pub fn setup_audio(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(SoundAssets {
+        music: asset_server.load("sounds/music.ogg"),
+        capture: asset_server.load("sounds/capture.ogg"),
+        node_lost: asset_server.load("sounds/node_lost.ogg"),
+    });
+}
+
+pub fn start_music(
+    mut commands: Commands,
+    sounds: Res<SoundAssets>,
+    asset_server: Res<AssetServer>,
+    audio_state: Res<AudioState>,
+    music_q: Query<(), With<Music>>,
+) {
+    if !music_q.is_empty() || !asset_server.is_loaded(&sounds.music) {
+        return;
+    }
+
+    let settings = if audio_state.muted {
+        PlaybackSettings::LOOP.muted()
+    } else {
+        PlaybackSettings::LOOP
+    };
+    commands.spawn((AudioPlayer::new(sounds.music.clone()), settings, Music));
+}
+
+pub fn toggle_mute(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    mut audio_state: ResMut<AudioState>,
+    mut music_q: Query<&mut AudioSink, With<Music>>,
+) {
+    if !bindings.just_pressed(&keyboard, Action::ToggleMute) {
+        return;
+    }
+
+    audio_state.muted = !audio_state.muted;
+    for mut sink in music_q.iter_mut() {
+        if audio_state.muted {
+            sink.mute();
+        } else {
+            sink.unmute();
+        }
+    }
+}
+
+pub fn play_capture_sounds(
+    mut commands: Commands,
+    mut captures: MessageReader<CaptureEvent>,
+    sounds: Res<SoundAssets>,
+    asset_server: Res<AssetServer>,
+    mut audio_state: ResMut<AudioState>,
+    time: Res<Time>,
+) {
+    let mut captured = false;
+    let mut lost = false;
+    for capture in captures.read() {
+        if capture.new_owner == Owner::PLAYER {
+            captured = true;
+        } else if capture.previous_owner == Owner::PLAYER {
+            lost = true;
+        }
+    }
+
+    if audio_state.muted {
+        return;
+    }
+
+    let now = time.elapsed_secs();
+    if captured
+        && asset_server.is_loaded(&sounds.capture)
+        && now - audio_state.last_capture >= SOUND_DEBOUNCE
+    {
+        audio_state.last_capture = now;
+        commands.spawn((
+            AudioPlayer::new(sounds.capture.clone()),
+            PlaybackSettings::DESPAWN,
+        ));
+    }
+    if lost
+        && asset_server.is_loaded(&sounds.node_lost)
+        && now - audio_state.last_node_lost >= SOUND_DEBOUNCE
+    {
+        audio_state.last_node_lost = now;
+        commands.spawn((
+            AudioPlayer::new(sounds.node_lost.clone()),
+            PlaybackSettings::DESPAWN,
+        ));
+    }
+}
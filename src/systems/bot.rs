@@ -0,0 +1,67 @@
+use bevy::ecs::system::{Query, Res, ResMut};
+
+use crate::{
+    bot::{BoardSnapshot, FlowCommand, NodeView, PlayerBot},
+    components::{GameNode, Owner},
+    resources::{AiTimer, ComputerGraph, FlowMap, GameState, Paused},
+};
+
+pub fn drive_player_bot(
+    mut bot: ResMut<PlayerBot>,
+    nodes_q: Query<&GameNode>,
+    graph_res: Res<ComputerGraph>,
+    mut flow_map: ResMut<FlowMap>,
+    ai_timer: Res<AiTimer>,
+    game_state: Res<GameState>,
+    paused: Res<Paused>,
+) {
+    if *game_state != GameState::Playing || paused.0 || !ai_timer.0.just_finished() {
+        return;
+    }
+
+    let graph = &graph_res.graph;
+    let snapshot = BoardSnapshot {
+        nodes: nodes_q
+            .iter()
+            .map(|node| {
+                let view = NodeView {
+                    index: node.index,
+                    owner: node.owner,
+                    kind: node.kind,
+                    hp: node.hp,
+                    max_hp: node.max_hp,
+                };
+                (node.index, view)
+            })
+            .collect(),
+        neighbors: graph
+            .node_indices()
+            .map(|idx| (idx, graph.neighbors(idx).collect()))
+            .collect(),
+        flows: flow_map.flows.clone(),
+    };
+    let is_player_node = |idx| {
+        snapshot
+            .nodes
+            .get(&idx)
+            .is_some_and(|node| node.owner == Owner::PLAYER)
+    };
+
+    for command in bot.0.decide(&snapshot) {
+        match command {
+            FlowCommand::Add { from, to } => {
+                let path = [from, to];
+                if graph.contains_edge(from, to)
+                    && is_player_node(from)
+                    && flow_map.saturated_hop(&path, is_player_node).is_none()
+                {
+                    flow_map.add_route(&path, is_player_node);
+                }
+            }
+            FlowCommand::Remove { from, to } => flow_map.remove_route(&[from, to]),
+            FlowCommand::ClearAll => {
+                flow_map.clear();
+            }
+        }
+    }
+}
@@ -0,0 +1,122 @@
+use bevy::{
+    camera::{Camera, Camera2d, Projection, ScalingMode},
+    ecs::{
+        change_detection::DetectChanges,
+        message::MessageReader,
+        query::With,
+        system::{Local, Query, Res},
+    },
+    input::{
+        ButtonInput,
+        keyboard::KeyCode,
+        mouse::{MouseScrollUnit, MouseWheel},
+    },
+    math::{Rect, Vec2},
+    time::{Real, Time},
+    transform::components::Transform,
+};
+
+use crate::resources::{Action, ComputerGraph, InteractionState, KeyBindings};
+
+const PAN_SPEED: f32 = 1.5;
+const PAN_LIMIT: f32 = 2.0;
+const ZOOM_STEP: f32 = 0.1;
+const MIN_ZOOM: f32 = 0.3;
+const MAX_ZOOM: f32 = 3.0;
+const FIT_MARGIN: f32 = 0.15;
+
+pub fn camera_control(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    mut wheel: MessageReader<MouseWheel>,
+    time: Res<Time<Real>>,
+    mut camera_q: Query<(&mut Transform, &mut Projection), With<Camera2d>>,
+    interaction: Res<InteractionState>,
+) {
+    let Ok((mut transform, mut projection)) = camera_q.single_mut() else {
+        return;
+    };
+    let Projection::Orthographic(ortho) = projection.as_mut() else {
+        return;
+    };
+
+    let scroll: f32 = wheel
+        .read()
+        .map(|event| match event.unit {
+            MouseScrollUnit::Line => event.y,
+            MouseScrollUnit::Pixel => event.y / 100.0,
+        })
+        .sum();
+    if scroll != 0.0 && interaction.hovered_flow.is_none() {
+        ortho.scale = (ortho.scale * (1.0 - scroll * ZOOM_STEP)).clamp(MIN_ZOOM, MAX_ZOOM);
+    }
+
+    let mut direction = Vec2::ZERO;
+    if bindings.pressed(&keyboard, Action::PanUp) {
+        direction.y += 1.0;
+    }
+    if bindings.pressed(&keyboard, Action::PanDown) {
+        direction.y -= 1.0;
+    }
+    if bindings.pressed(&keyboard, Action::PanLeft) {
+        direction.x -= 1.0;
+    }
+    if bindings.pressed(&keyboard, Action::PanRight) {
+        direction.x += 1.0;
+    }
+
+    if direction != Vec2::ZERO {
+        let delta = direction.normalize() * PAN_SPEED * ortho.scale * time.delta_secs();
+        let target = (transform.translation.truncate() + delta)
+            .clamp(Vec2::splat(-PAN_LIMIT), Vec2::splat(PAN_LIMIT));
+        transform.translation.x = target.x;
+        transform.translation.y = target.y;
+    }
+}
+
+pub fn fit_camera_to_graph(
+    graph_res: Res<ComputerGraph>,
+    mut camera_q: Query<(&Camera, &mut Transform, &mut Projection), With<Camera2d>>,
+    mut pending: Local<bool>,
+) {
+    if graph_res.is_changed() {
+        *pending = true;
+    }
+    if !*pending {
+        return;
+    }
+
+    let Ok((camera, mut transform, mut projection)) = camera_q.single_mut() else {
+        return;
+    };
+    let Some(viewport) = camera.logical_viewport_size() else {
+        return;
+    };
+    let Projection::Orthographic(ortho) = projection.as_mut() else {
+        return;
+    };
+    let ScalingMode::FixedVertical { viewport_height } = ortho.scaling_mode else {
+        return;
+    };
+    let Some(bounds) = graph_res
+        .graph
+        .node_weights()
+        .map(|node| Rect::from_center_size(node.position, Vec2::ZERO))
+        .reduce(|a, b| a.union(b))
+    else {
+        return;
+    };
+    *pending = false;
+
+    let size = bounds.size() + Vec2::splat(FIT_MARGIN * 2.0);
+    let aspect = viewport.x / viewport.y;
+    ortho.scale = (size.y / viewport_height)
+        .max(size.x / (viewport_height * aspect))
+        .clamp(MIN_ZOOM, MAX_ZOOM);
+
+    let center = bounds
+        .center()
+        .clamp(Vec2::splat(-PAN_LIMIT), Vec2::splat(PAN_LIMIT));
+    transform.translation.x = center.x;
+    transform.translation.y = center.y;
+}
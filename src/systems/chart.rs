@@ -0,0 +1,105 @@
+use bevy::{
+    camera::Camera,
+    color::{Alpha, Color},
+    ecs::system::{Query, Res, ResMut},
+    gizmos::gizmos::Gizmos,
+    input::{ButtonInput, keyboard::KeyCode},
+    math::{Rect, Vec2},
+    platform::collections::HashMap,
+    time::Time,
+    transform::components::GlobalTransform,
+};
+
+use crate::{
+    components::{GameNode, Owner},
+    resources::{
+        Action, ComputerGraph, GameConfig, GameState, KeyBindings, OwnershipHistory, Palette,
+        Paused, ShowOwnershipChart,
+    },
+};
+
+const CHART_SIZE: Vec2 = Vec2::new(220.0, 90.0);
+const CHART_MARGIN: f32 = 12.0;
+
+pub fn toggle_ownership_chart(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    mut show: ResMut<ShowOwnershipChart>,
+) {
+    if bindings.just_pressed(&keyboard, Action::ToggleOwnershipChart) {
+        show.0 = !show.0;
+    }
+}
+
+pub fn sample_ownership(
+    nodes_q: Query<&GameNode>,
+    mut history: ResMut<OwnershipHistory>,
+    time: Res<Time>,
+    game_state: Res<GameState>,
+    paused: Res<Paused>,
+) {
+    if *game_state != GameState::Playing || paused.0 {
+        return;
+    }
+
+    history.timer.tick(time.delta());
+    if !history.timer.just_finished() {
+        return;
+    }
+
+    let mut sample = HashMap::new();
+    for node in nodes_q.iter() {
+        *sample.entry(node.owner).or_default() += 1;
+    }
+    history.record(sample);
+}
+
+pub fn draw_ownership_chart(
+    mut gizmos: Gizmos,
+    camera_q: Query<(&Camera, &GlobalTransform)>,
+    history: Res<OwnershipHistory>,
+    show: Res<ShowOwnershipChart>,
+    graph_res: Res<ComputerGraph>,
+    config: Res<GameConfig>,
+    palette: Res<Palette>,
+) {
+    if !show.0 {
+        return;
+    }
+    let Ok((camera, cam_transform)) = camera_q.single() else {
+        return;
+    };
+    let Some(viewport) = camera.logical_viewport_size() else {
+        return;
+    };
+
+    let screen_min = Vec2::new(viewport.x - CHART_MARGIN - CHART_SIZE.x, CHART_MARGIN);
+    let screen_max = Vec2::new(viewport.x - CHART_MARGIN, CHART_MARGIN + CHART_SIZE.y);
+    let (Ok(corner_a), Ok(corner_b)) = (
+        camera.viewport_to_world_2d(cam_transform, screen_min),
+        camera.viewport_to_world_2d(cam_transform, screen_max),
+    ) else {
+        return;
+    };
+    let frame = Rect::from_corners(corner_a, corner_b);
+    gizmos.rect_2d(frame.center(), frame.size(), Color::srgb(0.4, 0.4, 0.4));
+
+    let total_nodes = graph_res.graph.node_count().max(1) as f32;
+    let step = frame.width() / (OwnershipHistory::MAX_SAMPLES - 1) as f32;
+    let to_chart = |slot: usize, count: usize| {
+        Vec2::new(
+            frame.min.x + slot as f32 * step,
+            frame.min.y + frame.height() * count as f32 / total_nodes,
+        )
+    };
+
+    for faction in 0..config.faction_count {
+        let owner = Owner::Faction(faction);
+        let points = history
+            .samples
+            .iter()
+            .enumerate()
+            .map(|(slot, sample)| to_chart(slot, sample.get(&owner).copied().unwrap_or(0)));
+        gizmos.linestrip_2d(points, owner.color(*palette).with_alpha(0.9));
+    }
+}
@@ -0,0 +1,75 @@
+use bevy::{
+    diagnostic::{DiagnosticsStore, FrameTimeDiagnosticsPlugin},
+    ecs::{
+        change_detection::DetectChanges,
+        entity::Entity,
+        query::With,
+        system::{Commands, Query, Res, ResMut},
+    },
+    input::{ButtonInput, keyboard::KeyCode},
+    text::TextFont,
+    ui::{GlobalZIndex, Node, PositionType, Val, widget::Text},
+};
+
+use crate::{
+    components::{DebugOverlay, GameEdge, GameNode, Packet},
+    resources::{Action, KeyBindings, ShowDebugOverlay},
+};
+
+pub fn toggle_debug_overlay(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    mut show: ResMut<ShowDebugOverlay>,
+) {
+    if bindings.just_pressed(&keyboard, Action::ToggleDebugOverlay) {
+        show.0 = !show.0;
+    }
+}
+
+pub fn update_debug_overlay(
+    mut commands: Commands,
+    show: Res<ShowDebugOverlay>,
+    diagnostics: Res<DiagnosticsStore>,
+    mut overlay_q: Query<(Entity, &mut Text), With<DebugOverlay>>,
+    nodes_q: Query<(), With<GameNode>>,
+    edges_q: Query<(), With<GameEdge>>,
+    packets_q: Query<(), With<Packet>>,
+) {
+    if show.is_changed() {
+        for (entity, _) in overlay_q.iter() {
+            commands.entity(entity).despawn();
+        }
+        if show.0 {
+            commands.spawn((
+                Text::default(),
+                TextFont {
+                    font_size: 16.0,
+                    ..TextFont::default()
+                },
+                Node {
+                    position_type: PositionType::Absolute,
+                    bottom: Val::Px(12.0),
+                    left: Val::Px(12.0),
+                    ..Node::default()
+                },
+                GlobalZIndex(100),
+                DebugOverlay,
+            ));
+        }
+        return;
+    }
+
+    let fps = diagnostics
+        .get(&FrameTimeDiagnosticsPlugin::FPS)
+        .and_then(|fps| fps.smoothed())
+        .unwrap_or(0.0);
+
+    for (_, mut text) in overlay_q.iter_mut() {
+        text.0 = format!(
+            "FPS: {fps:.0}\nNodes: {}\nEdges: {}\nPackets: {}",
+            nodes_q.iter().len(),
+            edges_q.iter().len(),
+            packets_q.iter().len()
+        );
+    }
+}
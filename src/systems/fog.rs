@@ -0,0 +1,47 @@
+use bevy::{
+    ecs::system::{Res, ResMut},
+    input::{ButtonInput, keyboard::KeyCode},
+    platform::collections::HashSet,
+};
+use petgraph::graph::NodeIndex;
+use std::collections::VecDeque;
+
+use crate::resources::{Action, ComputerGraph, FogOfWar, KeyBindings};
+
+pub fn toggle_fog(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    mut fog: ResMut<FogOfWar>,
+) {
+    if bindings.just_pressed(&keyboard, Action::ToggleFog) {
+        fog.enabled = !fog.enabled;
+    }
+}
+
+pub fn visible_nodes(
+    graph_res: &ComputerGraph,
+    owned: impl IntoIterator<Item = NodeIndex>,
+    radius: usize,
+) -> HashSet<NodeIndex> {
+    let mut visible = HashSet::new();
+    let mut queue = VecDeque::new();
+
+    for node in owned {
+        if visible.insert(node) {
+            queue.push_back((node, 0));
+        }
+    }
+
+    while let Some((node, depth)) = queue.pop_front() {
+        if depth >= radius {
+            continue;
+        }
+        for neighbor in graph_res.graph.neighbors(node) {
+            if visible.insert(neighbor) {
+                queue.push_back((neighbor, depth + 1));
+            }
+        }
+    }
+
+    visible
+}
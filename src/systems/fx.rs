@@ -0,0 +1,127 @@
+use bevy::{
+    asset::Assets,
+    color::{Alpha, Mix},
+    ecs::{
+        entity::Entity,
+        message::MessageReader,
+        system::{Commands, Query, Res, ResMut},
+    },
+    input::{ButtonInput, keyboard::KeyCode},
+    math::{Vec3, primitives::Annulus},
+    mesh::{Mesh, Mesh2d},
+    sprite_render::{AlphaMode2d, ColorMaterial, MeshMaterial2d},
+    time::{Time, Timer, TimerMode},
+    transform::components::Transform,
+};
+
+use crate::{
+    components::{CaptureRing, GameNode, Trail},
+    events::{CaptureEvent, FireEvent},
+    resources::{
+        Action, FogOfWar, GraphEntityMap, KeyBindings, NodePositions, Palette, ShowTrails,
+    },
+};
+
+const RING_DURATION: f32 = 0.4;
+const RING_MAX_SCALE: f32 = 3.0;
+
+pub fn play_capture_fx(
+    mut commands: Commands,
+    mut captures: MessageReader<CaptureEvent>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    positions: Res<NodePositions>,
+    palette: Res<Palette>,
+    fog: Res<FogOfWar>,
+) {
+    for capture in captures.read() {
+        if !fog.is_visible(capture.node) {
+            continue;
+        }
+        let Some(&pos) = positions.0.get(&capture.node) else {
+            continue;
+        };
+
+        commands.spawn((
+            Mesh2d(meshes.add(Annulus::new(0.06, 0.075))),
+            MeshMaterial2d(materials.add(ColorMaterial {
+                color: capture.previous_owner.color(*palette),
+                alpha_mode: AlphaMode2d::Blend,
+                ..ColorMaterial::default()
+            })),
+            Transform::from_xyz(pos.x, pos.y, 2.0),
+            CaptureRing {
+                timer: Timer::from_seconds(RING_DURATION, TimerMode::Once),
+                from_color: capture.previous_owner.color(*palette),
+                to_color: capture.new_owner.color(*palette),
+            },
+        ));
+    }
+}
+
+pub fn play_fire_fx(
+    mut fires: MessageReader<FireEvent>,
+    entity_map: Res<GraphEntityMap>,
+    mut nodes_q: Query<&mut GameNode>,
+) {
+    for fire in fires.read() {
+        if let Some(&entity) = entity_map.nodes.get(&fire.node)
+            && let Ok(mut node) = nodes_q.get_mut(entity)
+        {
+            node.begin_fire_flash();
+        }
+    }
+}
+
+pub fn animate_capture_rings(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut rings_q: Query<(
+        Entity,
+        &mut CaptureRing,
+        &mut Transform,
+        &MeshMaterial2d<ColorMaterial>,
+    )>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    for (entity, mut ring, mut transform, mat_handle) in rings_q.iter_mut() {
+        ring.timer.tick(time.delta());
+        if ring.timer.is_finished() {
+            commands.entity(entity).despawn();
+            continue;
+        }
+
+        let t = ring.timer.fraction();
+        transform.scale = Vec3::splat(1.0 + (RING_MAX_SCALE - 1.0) * t);
+        if let Some(material) = materials.get_mut(mat_handle) {
+            material.color = ring.from_color.mix(&ring.to_color, t).with_alpha(1.0 - t);
+        }
+    }
+}
+
+pub fn toggle_trails(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    mut show: ResMut<ShowTrails>,
+) {
+    if bindings.just_pressed(&keyboard, Action::ToggleTrails) {
+        show.0 = !show.0;
+        println!("Packet trails: {}", if show.0 { "on" } else { "off" });
+    }
+}
+
+pub fn fade_trails(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut trails_q: Query<(Entity, &mut Trail, &mut Transform)>,
+) {
+    for (entity, mut trail, mut transform) in trails_q.iter_mut() {
+        trail.life.tick(time.delta());
+        if trail.life.is_finished() {
+            commands.entity(entity).despawn();
+            continue;
+        }
+
+        transform.scale = Vec3::splat(1.0 - trail.life.fraction());
+    }
+}
@@ -0,0 +1,84 @@
+use bevy::{
+    color::{Alpha, Color},
+    ecs::system::{Query, Res},
+    gizmos::gizmos::Gizmos,
+    platform::collections::HashMap,
+    transform::components::Transform,
+};
+use petgraph::graph::NodeIndex;
+
+use crate::{
+    components::{GameNode, Owner, Packet},
+    resources::{
+        ComputerGraph, FlowMap, FogOfWar, GameConfig, InteractionState, Palette, ShowFrontier,
+    },
+};
+
+pub fn draw_graph_gizmos(
+    mut gizmos: Gizmos,
+    graph_res: Res<ComputerGraph>,
+    flow_map: Res<FlowMap>,
+    interaction: Res<InteractionState>,
+    fog: Res<FogOfWar>,
+    packets_q: Query<(&Transform, &Packet)>,
+    nodes_q: Query<&GameNode>,
+    show_frontier: Res<ShowFrontier>,
+    config: Res<GameConfig>,
+    palette: Res<Palette>,
+) {
+    let graph = &graph_res.graph;
+    let owners: HashMap<NodeIndex, Owner> = nodes_q
+        .iter()
+        .map(|node| (node.index, node.owner))
+        .collect();
+    let is_frontier = |a, b| {
+        let (Some(&owner_a), Some(&owner_b)) = (owners.get(&a), owners.get(&b)) else {
+            return false;
+        };
+        show_frontier.0
+            && owner_a != owner_b
+            && owner_a != Owner::Neutral
+            && owner_b != Owner::Neutral
+            && fog.is_visible(a)
+            && fog.is_visible(b)
+    };
+    let has_flow = |from, to| flow_map.flows.get(&from).is_some_and(|t| t.contains(&to));
+    let on_path = |a, b| {
+        interaction
+            .path
+            .windows(2)
+            .any(|hop| (hop[0] == a && hop[1] == b) || (hop[0] == b && hop[1] == a))
+    };
+
+    for edge_idx in graph.edge_indices() {
+        let Some((u, v)) = graph.edge_endpoints(edge_idx) else {
+            continue;
+        };
+
+        let color = if on_path(u, v) {
+            palette.path_edge_color(false)
+        } else if has_flow(u, v) || has_flow(v, u) {
+            Color::srgb(0.0, 2.5, 10.0)
+        } else if interaction.hovered_edge == Some(edge_idx) {
+            Color::srgb(2.0, 2.0, 2.0)
+        } else if is_frontier(u, v) {
+            Color::srgb(6.0, 2.0, 0.0)
+        } else {
+            Color::srgb(0.2, 0.2, 0.2)
+        };
+        gizmos.line_2d(graph[u].position, graph[v].position, color);
+    }
+
+    for (transform, packet) in packets_q.iter() {
+        let color = if fog.is_visible(packet.from) || fog.is_visible(packet.to) {
+            packet.owner.packet_color(*palette)
+        } else {
+            Color::srgb(0.3, 0.3, 0.3).with_alpha(0.5)
+        };
+        gizmos.circle_2d(
+            transform.translation.truncate(),
+            config.packet_radius,
+            color,
+        );
+    }
+}
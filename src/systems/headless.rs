@@ -0,0 +1,29 @@
+use bevy::{
+    app::AppExit,
+    ecs::{
+        message::MessageWriter,
+        system::{Local, Res},
+    },
+};
+
+use crate::{FIXED_TIMESTEP, resources::GameState};
+
+const MAX_TICKS: u64 = (30.0 * 60.0 / FIXED_TIMESTEP) as u64;
+
+pub fn report_headless_result(
+    game_state: Res<GameState>,
+    mut ticks: Local<u64>,
+    mut exit: MessageWriter<AppExit>,
+) {
+    *ticks += 1;
+
+    let winner = match *game_state {
+        GameState::Playing if *ticks < MAX_TICKS => return,
+        GameState::Playing => "nobody (tick limit reached)",
+        GameState::PlayerWon => "player",
+        GameState::PlayerLost => "AI",
+    };
+
+    println!("Winner: {winner} after {} ticks", *ticks);
+    exit.write(AppExit::Success);
+}
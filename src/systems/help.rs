@@ -0,0 +1,65 @@
+use bevy::{
+    color::Color,
+    ecs::{
+        change_detection::DetectChanges,
+        entity::Entity,
+        query::With,
+        system::{Commands, Query, Res, ResMut},
+    },
+    input::{ButtonInput, keyboard::KeyCode},
+    text::{TextColor, TextFont},
+    ui::{BackgroundColor, GlobalZIndex, Node, PositionType, UiRect, Val, widget::Text},
+};
+
+use crate::{
+    components::HelpUi,
+    resources::{Action, KeyBindings, ShowHelp},
+};
+
+pub fn toggle_help(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    mut show: ResMut<ShowHelp>,
+) {
+    if bindings.just_pressed(&keyboard, Action::ToggleHelp) {
+        show.0 = !show.0;
+    }
+}
+
+pub fn show_help_ui(
+    mut commands: Commands,
+    show: Res<ShowHelp>,
+    bindings: Res<KeyBindings>,
+    panel_q: Query<Entity, With<HelpUi>>,
+) {
+    if !show.is_changed() && !bindings.is_changed() {
+        return;
+    }
+
+    for entity in panel_q.iter() {
+        commands.entity(entity).despawn();
+    }
+
+    if !show.0 {
+        return;
+    }
+
+    commands.spawn((
+        Text::new(bindings.help_lines().join("\n")),
+        TextFont {
+            font_size: 14.0,
+            ..TextFont::default()
+        },
+        TextColor(Color::srgb(0.9, 0.9, 0.9)),
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(48.0),
+            left: Val::Px(12.0),
+            padding: UiRect::all(Val::Px(8.0)),
+            ..Node::default()
+        },
+        BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.8)),
+        GlobalZIndex(200),
+        HelpUi,
+    ));
+}
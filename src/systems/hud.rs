@@ -0,0 +1,130 @@
+use bevy::{
+    ecs::{
+        hierarchy::ChildSpawnerCommands,
+        query::{With, Without},
+        system::{Commands, Query, Res},
+    },
+    text::{TextColor, TextFont},
+    ui::{JustifyContent, Node, PositionType, UiRect, Val, widget::Text},
+};
+
+use crate::{
+    components::{GameNode, HudClock, HudCounter, HudSpeed, Owner, Packet},
+    resources::{GameConfig, MatchClock, Palette, TimeScale, VictoryMode},
+};
+
+pub fn setup_hud(
+    mut commands: Commands,
+    config: Res<GameConfig>,
+    victory_mode: Res<VictoryMode>,
+    palette: Res<Palette>,
+) {
+    let counter = |parent: &mut ChildSpawnerCommands, owner: Owner| {
+        parent.spawn((
+            Text::default(),
+            TextFont {
+                font_size: 18.0,
+                ..TextFont::default()
+            },
+            TextColor(owner.color(*palette)),
+            Node {
+                margin: UiRect::horizontal(Val::Px(16.0)),
+                ..Node::default()
+            },
+            HudCounter { owner },
+        ));
+    };
+
+    commands
+        .spawn(Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(8.0),
+            width: Val::Percent(100.0),
+            justify_content: JustifyContent::Center,
+            ..Node::default()
+        })
+        .with_children(|parent| {
+            for faction in 0..config.faction_count {
+                counter(parent, Owner::Faction(faction));
+            }
+            counter(parent, Owner::Neutral);
+
+            if *victory_mode != VictoryMode::Annihilation {
+                parent.spawn((
+                    Text::default(),
+                    TextFont {
+                        font_size: 18.0,
+                        ..TextFont::default()
+                    },
+                    Node {
+                        margin: UiRect::horizontal(Val::Px(16.0)),
+                        ..Node::default()
+                    },
+                    HudClock,
+                ));
+            }
+
+            parent.spawn((
+                Text::default(),
+                TextFont {
+                    font_size: 18.0,
+                    ..TextFont::default()
+                },
+                Node {
+                    margin: UiRect::horizontal(Val::Px(16.0)),
+                    ..Node::default()
+                },
+                HudSpeed,
+            ));
+        });
+}
+
+pub fn update_hud(
+    nodes_q: Query<&GameNode>,
+    packets_q: Query<&Packet>,
+    mut counters_q: Query<(&HudCounter, &mut Text, &mut TextColor)>,
+    mut clock_q: Query<&mut Text, (With<HudClock>, Without<HudCounter>)>,
+    mut speed_q: Query<&mut Text, (With<HudSpeed>, Without<HudCounter>, Without<HudClock>)>,
+    clock: Res<MatchClock>,
+    time_scale: Res<TimeScale>,
+    victory_mode: Res<VictoryMode>,
+    palette: Res<Palette>,
+) {
+    for (counter, mut text, mut color) in counters_q.iter_mut() {
+        color.0 = counter.owner.color(*palette);
+        let nodes = nodes_q.iter().filter(|n| n.owner == counter.owner).count();
+        let packets = packets_q
+            .iter()
+            .filter(|p| p.owner == counter.owner)
+            .count();
+
+        let label = counter.owner.name();
+        text.0 = if counter.owner == Owner::Neutral {
+            format!("{label}: {nodes} nodes")
+        } else {
+            format!("{label}: {nodes} nodes / {packets} packets")
+        };
+    }
+
+    for mut text in clock_q.iter_mut() {
+        text.0 = match *victory_mode {
+            VictoryMode::Annihilation => String::new(),
+            VictoryMode::Majority { hold_secs, .. } => match clock.majority {
+                Some((owner, held)) => format!(
+                    "{} majority: {:.0}s left",
+                    owner.name(),
+                    (hold_secs - held).max(0.0).ceil()
+                ),
+                None => "No majority".to_string(),
+            },
+            VictoryMode::Timed { duration_secs } => {
+                let remaining = (duration_secs - clock.elapsed).max(0.0).ceil() as u32;
+                format!("Time left: {}:{:02}", remaining / 60, remaining % 60)
+            }
+        };
+    }
+
+    for mut text in speed_q.iter_mut() {
+        text.0 = format!("Speed x{}", time_scale.0);
+    }
+}
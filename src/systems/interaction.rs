@@ -1,18 +1,31 @@
 use bevy::{
     camera::Camera,
     ecs::{
+        message::MessageReader,
         query::With,
         system::{Query, Res, ResMut},
     },
-    input::{ButtonInput, keyboard::KeyCode, mouse::MouseButton},
+    input::{
+        ButtonInput,
+        keyboard::KeyCode,
+        mouse::{MouseButton, MouseScrollUnit, MouseWheel},
+    },
+    math::{Rect, Vec2},
     transform::components::GlobalTransform,
     window::{PrimaryWindow, Window},
 };
-use petgraph::algo::astar;
+use petgraph::{
+    algo::{astar, dijkstra},
+    graph::NodeIndex,
+    visit::NodeFiltered,
+};
 
 use crate::{
     components::{GameNode, Owner},
-    resources::{ComputerGraph, FlowMap, GraphEntityMap, InteractionState},
+    resources::{
+        Action, AllowEnemyRoutes, ComputerGraph, ControlGroups, FlowEdit, FlowHistory, FlowMap,
+        GameConfig, GraphEntityMap, InteractionState, KeyBindings, SandboxMode,
+    },
 };
 
 pub fn handle_interaction(
@@ -21,11 +34,83 @@ pub fn handle_interaction(
     mut state: ResMut<InteractionState>,
     mouse_buttons: Res<ButtonInput<MouseButton>>,
     keyboard: Res<ButtonInput<KeyCode>>,
+    bindings: Res<KeyBindings>,
     graph_res: Res<ComputerGraph>,
     nodes_q: Query<&mut GameNode>,
     entity_map: Res<GraphEntityMap>,
     mut flow_map: ResMut<FlowMap>,
+    mut flow_history: ResMut<FlowHistory>,
+    mut wheel: MessageReader<MouseWheel>,
+    mut allow_enemy_routes: ResMut<AllowEnemyRoutes>,
+    config: Res<GameConfig>,
+    sandbox: Res<SandboxMode>,
 ) {
+    const EDGE_HOVER_DIST: f32 = 0.03;
+    const HOVER_MARGIN: f32 = 0.02;
+    const WEIGHT_STEP: f32 = 1.25;
+
+    if !bindings.mouse_pressed(&mouse_buttons, Action::Select)
+        && !bindings.mouse_just_released(&mouse_buttons, Action::Select)
+    {
+        state.dragging_from = None;
+        state.selection_box = None;
+    }
+
+    if bindings.just_pressed(&keyboard, Action::ToggleEnemyRoutes) {
+        allow_enemy_routes.0 = !allow_enemy_routes.0;
+        state.path_query = None;
+        println!(
+            "Routing through enemy nodes: {}",
+            if allow_enemy_routes.0 { "on" } else { "off" }
+        );
+    }
+
+    let is_player_node = |idx: NodeIndex| {
+        entity_map
+            .nodes
+            .get(&idx)
+            .and_then(|&entity| nodes_q.get(entity).ok())
+            .is_some_and(|node| node.owner == Owner::PLAYER)
+    };
+
+    if bindings.just_pressed(&keyboard, Action::ClearFlows) {
+        let before = flow_map.clone();
+        let removed = flow_map.clear();
+        flow_history.record(FlowEdit::between(&before, &flow_map));
+        println!("Removed all {removed} flow(s)");
+    }
+
+    if bindings.just_pressed(&keyboard, Action::Retreat) {
+        let before = flow_map.clone();
+        let mut retreated = 0;
+        state.selected_sources.retain(|&idx| is_player_node(idx));
+
+        for &source in &state.selected_sources {
+            let Some(path) = retreat_path(&graph_res, source, is_player_node) else {
+                continue;
+            };
+            for &node in &path[..path.len() - 1] {
+                flow_map.clear_node(node);
+            }
+            flow_map.add_route(&path, is_player_node);
+            retreated += 1;
+        }
+
+        flow_history.record(FlowEdit::between(&before, &flow_map));
+        println!("Retreating {retreated} node(s) toward the rear");
+    }
+
+    if bindings.just_pressed(&keyboard, Action::Undo)
+        && !flow_history.undo(&mut flow_map, is_player_node)
+    {
+        println!("Nothing to undo");
+    }
+    if bindings.just_pressed(&keyboard, Action::Redo)
+        && !flow_history.redo(&mut flow_map, is_player_node)
+    {
+        println!("Nothing to redo");
+    }
+
     let Ok((camera, cam_transform)) = camera_q.single() else {
         return;
     };
@@ -40,70 +125,253 @@ pub fn handle_interaction(
     };
     let world_pos = ray.origin.truncate();
 
-    let mut hovered = None;
-    let mut min_dist = 0.1;
+    let hovered = nodes_q
+        .iter()
+        .map(|node| {
+            (
+                node,
+                graph_res.graph[node.index].position.distance(world_pos),
+            )
+        })
+        .filter(|(node, dist)| *dist < node.kind.radius() * node.visual_scale() + HOVER_MARGIN)
+        .min_by(|a, b| a.1.total_cmp(&b.1).then(a.0.index.cmp(&b.0.index)))
+        .map(|(node, _)| node.index);
+    state.hovered_node = hovered;
 
-    for node_idx in graph_res.0.node_indices() {
-        let pos = graph_res.0[node_idx].position;
-        let dist = pos.distance(world_pos);
-        if dist < min_dist {
-            min_dist = dist;
-            hovered = Some(node_idx);
-        }
+    let graph = &graph_res.graph;
+    state.hovered_edge = if hovered.is_some() {
+        None
+    } else {
+        graph
+            .edge_indices()
+            .filter_map(|edge_idx| {
+                let (u, v) = graph.edge_endpoints(edge_idx)?;
+                let dist = distance_to_segment(world_pos, graph[u].position, graph[v].position);
+                Some((edge_idx, dist))
+            })
+            .filter(|&(_, dist)| dist < EDGE_HOVER_DIST)
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(edge_idx, _)| edge_idx)
+    };
+    state.hovered_flow = if hovered.is_some() {
+        None
+    } else {
+        flow_map
+            .flows
+            .iter()
+            .flat_map(|(&source, targets)| targets.iter().map(move |&target| (source, target)))
+            .map(|(source, target)| {
+                let dist =
+                    distance_to_segment(world_pos, graph[source].position, graph[target].position);
+                ((source, target), dist)
+            })
+            .filter(|&(_, dist)| dist < EDGE_HOVER_DIST)
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(flow, _)| flow)
+    };
+
+    let scroll: f32 = wheel
+        .read()
+        .map(|event| match event.unit {
+            MouseScrollUnit::Line => event.y,
+            MouseScrollUnit::Pixel => event.y / 100.0,
+        })
+        .sum();
+    if scroll != 0.0
+        && let Some((source, target)) = state.hovered_flow
+    {
+        let before = flow_map.clone();
+        let weight = flow_map.adjust_weight(source, target, WEIGHT_STEP.powf(scroll));
+        flow_history.record(FlowEdit::between(&before, &flow_map));
+        println!(
+            "Flow {} -> {} weight: {weight:.2}",
+            source.index(),
+            target.index()
+        );
     }
-    state.hovered_node = hovered;
 
-    if mouse_buttons.just_pressed(MouseButton::Left) {
-        if let Some(idx) = hovered {
-            if let Some(&entity) = entity_map.nodes.get(&idx) {
-                if let Ok(node) = nodes_q.get(entity) {
-                    if node.owner == Owner::Player {
-                        state.selected_source = Some(idx);
-                    }
-                }
-            }
+    let is_passable = |idx: NodeIndex| {
+        allow_enemy_routes.0
+            || entity_map
+                .nodes
+                .get(&idx)
+                .and_then(|&entity| nodes_q.get(entity).ok())
+                .is_none_or(|node| !node.owner.is_ai())
+    };
+    let is_erasing = bindings.pressed(&keyboard, Action::Erase);
+    let is_box_selecting = bindings.pressed(&keyboard, Action::BoxSelect);
+
+    if bindings.mouse_just_pressed(&mouse_buttons, Action::Select) && !sandbox.0 {
+        if is_box_selecting {
+            state.dragging_from = None;
+            state.selection_box = Some((world_pos, world_pos));
         } else {
-            state.selected_source = None;
+            state.dragging_from = hovered.filter(|&idx| is_player_node(idx));
+            if state.dragging_from.is_none() {
+                state.selected_sources.clear();
+            }
         }
     }
 
-    state.path.clear();
-    if let (Some(source), Some(target)) = (state.selected_source, state.hovered_node) {
-        if source != target {
-            let path_result = astar(
-                &graph_res.0,
-                source,
-                |finish| finish == target,
-                |_| 1.0,
-                |_| 0.0,
-            );
-            if let Some((_, path)) = path_result {
-                state.path = path;
+    if let Some((start, _)) = state.selection_box {
+        state.selection_box = Some((start, world_pos));
+    }
+
+    state.refresh_path(|source, target| find_path(&graph_res, source, target, is_passable));
+
+    if bindings.mouse_just_released(&mouse_buttons, Action::Select) {
+        if state.dragging_from.take().is_some() {
+            let before = flow_map.clone();
+            if is_erasing {
+                flow_map.remove_route(&state.path);
+            } else if let Some(node) = flow_map.saturated_hop(&state.path, is_player_node) {
+                report_saturated(node, config.max_flows_per_node);
+            } else {
+                flow_map.add_route(&state.path, is_player_node);
             }
+            flow_history.record(FlowEdit::between(&before, &flow_map));
         }
+
+        if let Some((start, end)) = state.selection_box.take() {
+            let area = Rect::from_corners(start, end);
+            state.selected_sources = graph_res
+                .graph
+                .node_indices()
+                .filter(|&idx| area.contains(graph_res.graph[idx].position) && is_player_node(idx))
+                .collect();
+        }
+    }
+
+    if bindings.mouse_just_pressed(&mouse_buttons, Action::ClearNodeFlows)
+        && let Some(idx) = hovered
+    {
+        let before = flow_map.clone();
+        let removed = flow_map.clear_node(idx);
+        flow_history.record(FlowEdit::between(&before, &flow_map));
+        println!("Removed {removed} flow(s) from node {}", idx.index());
     }
 
-    if mouse_buttons.just_pressed(MouseButton::Right) {
-        if !state.path.is_empty() {
-            let is_erasing =
-                keyboard.pressed(KeyCode::ShiftLeft) || keyboard.pressed(KeyCode::ShiftRight);
-
-            for window in state.path.windows(2) {
-                let current_node = window[0];
-                let next_node = window[1];
-
-                if is_erasing {
-                    if let Some(targets) = flow_map.flows.get_mut(&current_node) {
-                        targets.remove(&next_node);
-                        if targets.is_empty() {
-                            flow_map.flows.remove(&current_node);
-                        }
-                    }
-                } else {
-                    let entry = flow_map.flows.entry(current_node).or_default();
-                    entry.insert(next_node);
-                }
+    if bindings.mouse_just_pressed(&mouse_buttons, Action::RouteSelected)
+        && let Some(target) = hovered
+    {
+        let before = flow_map.clone();
+        state.selected_sources.retain(|&idx| is_player_node(idx));
+
+        for &source in &state.selected_sources {
+            let Some(path) = find_path(&graph_res, source, target, is_passable) else {
+                continue;
+            };
+
+            if is_erasing {
+                flow_map.remove_route(&path);
+            } else if let Some(node) = flow_map.saturated_hop(&path, is_player_node) {
+                report_saturated(node, config.max_flows_per_node);
+            } else {
+                flow_map.add_route(&path, is_player_node);
             }
         }
+
+        flow_history.record(FlowEdit::between(&before, &flow_map));
+    }
+}
+
+pub fn handle_control_groups(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    mut state: ResMut<InteractionState>,
+    mut groups: ResMut<ControlGroups>,
+    nodes_q: Query<&GameNode>,
+    entity_map: Res<GraphEntityMap>,
+) {
+    let Some(group) = bindings
+        .just_pressed_slot(&keyboard, Action::ControlGroup)
+        .map(|slot| slot as u8 + 1)
+    else {
+        return;
+    };
+
+    if bindings.pressed(&keyboard, Action::BoxSelect) {
+        groups.0.insert(group, state.selected_sources.clone());
+        println!(
+            "Stored {} node(s) in group {group}",
+            state.selected_sources.len()
+        );
+        return;
     }
+
+    let Some(members) = groups.0.get_mut(&group) else {
+        return;
+    };
+    members.retain(|idx| {
+        entity_map
+            .nodes
+            .get(idx)
+            .and_then(|&entity| nodes_q.get(entity).ok())
+            .is_some_and(|node| node.owner == Owner::PLAYER)
+    });
+    state.selected_sources = members.clone();
+    println!("Selected {} node(s) from group {group}", members.len());
+}
+
+fn report_saturated(node: NodeIndex, max_flows: usize) {
+    println!(
+        "Node {} already has {max_flows} flow(s); remove one before adding another",
+        node.index()
+    );
+}
+
+fn find_path(
+    graph_res: &ComputerGraph,
+    source: NodeIndex,
+    target: NodeIndex,
+    is_passable: impl Fn(NodeIndex) -> bool,
+) -> Option<Vec<NodeIndex>> {
+    if source == target {
+        return None;
+    }
+
+    let graph = &graph_res.graph;
+    let target_pos = graph[target].position;
+    let filtered = NodeFiltered::from_fn(graph, |node| {
+        node == source || node == target || is_passable(node)
+    });
+
+    astar(
+        &filtered,
+        source,
+        |finish| finish == target,
+        |edge| edge.weight().length,
+        |node| graph[node].position.distance(target_pos),
+    )
+    .map(|(_, path)| path)
+}
+
+fn retreat_path(
+    graph_res: &ComputerGraph,
+    source: NodeIndex,
+    is_player_node: impl Fn(NodeIndex) -> bool,
+) -> Option<Vec<NodeIndex>> {
+    let graph = &graph_res.graph;
+    let is_rear = |idx: NodeIndex| graph.neighbors(idx).all(&is_player_node);
+    if is_rear(source) {
+        return None;
+    }
+
+    let owned = NodeFiltered::from_fn(graph, &is_player_node);
+    let distances = dijkstra(&owned, source, None, |edge| edge.weight().length);
+    let base = graph
+        .node_indices()
+        .filter(|&idx| is_player_node(idx) && is_rear(idx))
+        .filter_map(|idx| distances.get(&idx).map(|&dist| (idx, dist)))
+        .min_by(|a, b| a.1.total_cmp(&b.1).then(a.0.cmp(&b.0)))
+        .map(|(idx, _)| idx)?;
+
+    find_path(graph_res, source, base, &is_player_node)
+}
+
+fn distance_to_segment(point: Vec2, start: Vec2, end: Vec2) -> f32 {
+    let segment = end - start;
+    let t =
+        ((point - start).dot(segment) / segment.length_squared().max(f32::EPSILON)).clamp(0.0, 1.0);
+    point.distance(start + segment * t)
 }
@@ -0,0 +1,131 @@
+use bevy::{
+    color::Color,
+    ecs::{
+        change_detection::DetectChanges,
+        entity::Entity,
+        query::With,
+        system::{Commands, Query, Res, ResMut},
+    },
+    input::{ButtonInput, keyboard::KeyCode},
+    math::Vec3,
+    platform::collections::{HashMap, HashSet},
+    sprite::Text2d,
+    text::{TextColor, TextFont},
+    transform::components::Transform,
+};
+use petgraph::graph::NodeIndex;
+
+use crate::{
+    components::{GameNode, HpLabel, IndexLabel},
+    resources::{Action, ComputerGraph, FogOfWar, KeyBindings, ShowHpLabels, ShowIndexLabels},
+};
+
+const LABEL_OFFSET_Y: f32 = 0.1;
+const LABEL_SCALE: f32 = 0.003;
+
+pub fn toggle_hp_labels(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    mut show: ResMut<ShowHpLabels>,
+) {
+    if bindings.just_pressed(&keyboard, Action::ToggleHpLabels) {
+        show.0 = !show.0;
+    }
+}
+
+pub fn toggle_index_labels(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    mut show: ResMut<ShowIndexLabels>,
+) {
+    if bindings.just_pressed(&keyboard, Action::ToggleIndexLabels) {
+        show.0 = !show.0;
+    }
+}
+
+pub fn update_index_labels(
+    mut commands: Commands,
+    show: Res<ShowIndexLabels>,
+    labels_q: Query<Entity, With<IndexLabel>>,
+    graph_res: Res<ComputerGraph>,
+) {
+    if !show.is_changed() && !graph_res.is_changed() {
+        return;
+    }
+
+    for entity in labels_q.iter() {
+        commands.entity(entity).despawn();
+    }
+    if !show.0 {
+        return;
+    }
+
+    for node_idx in graph_res.graph.node_indices() {
+        let pos = graph_res.graph[node_idx].position;
+        commands.spawn((
+            Text2d::new(format!("#{}", node_idx.index())),
+            TextFont {
+                font_size: 28.0,
+                ..TextFont::default()
+            },
+            TextColor(Color::srgb(0.7, 0.7, 0.7)),
+            Transform::from_xyz(pos.x, pos.y - LABEL_OFFSET_Y, 5.0)
+                .with_scale(Vec3::splat(LABEL_SCALE)),
+            IndexLabel,
+        ));
+    }
+}
+
+pub fn update_hp_labels(
+    mut commands: Commands,
+    show: Res<ShowHpLabels>,
+    nodes_q: Query<&GameNode>,
+    mut labels_q: Query<(Entity, &HpLabel, &mut Text2d, &mut Transform)>,
+    graph_res: Res<ComputerGraph>,
+    fog: Res<FogOfWar>,
+) {
+    if !show.0 {
+        for (entity, ..) in labels_q.iter() {
+            commands.entity(entity).despawn();
+        }
+        return;
+    }
+
+    let hp_by_node: HashMap<NodeIndex, f32> = nodes_q
+        .iter()
+        .filter(|n| fog.is_visible(n.index))
+        .map(|n| (n.index, n.hp))
+        .collect();
+    let mut labelled = HashSet::new();
+
+    for (entity, label, mut text, mut transform) in labels_q.iter_mut() {
+        let Some(hp) = hp_by_node.get(&label.node) else {
+            commands.entity(entity).despawn();
+            continue;
+        };
+
+        let pos = graph_res.graph[label.node].position;
+        transform.translation.x = pos.x;
+        transform.translation.y = pos.y + LABEL_OFFSET_Y;
+        text.0 = format!("{}", hp.round());
+        labelled.insert(label.node);
+    }
+
+    for (&node_idx, hp) in &hp_by_node {
+        if labelled.contains(&node_idx) {
+            continue;
+        }
+
+        let pos = graph_res.graph[node_idx].position;
+        commands.spawn((
+            Text2d::new(format!("{}", hp.round())),
+            TextFont {
+                font_size: 32.0,
+                ..TextFont::default()
+            },
+            Transform::from_xyz(pos.x, pos.y + LABEL_OFFSET_Y, 5.0)
+                .with_scale(Vec3::splat(LABEL_SCALE)),
+            HpLabel { node: node_idx },
+        ));
+    }
+}
@@ -0,0 +1,402 @@
+use bevy::{
+    asset::Assets,
+    color::Color,
+    ecs::{
+        entity::Entity,
+        query::{Or, With},
+        system::SystemParam,
+        system::{Commands, Query, Res, ResMut},
+    },
+    input::{ButtonInput, keyboard::KeyCode},
+    math::{Quat, Vec3, primitives::Circle, primitives::Rectangle, primitives::RegularPolygon},
+    mesh::{Mesh, Mesh2d},
+    sprite_render::{ColorMaterial, MeshMaterial2d},
+    transform::components::Transform,
+};
+use petgraph::{algo::dijkstra, graph::NodeIndex};
+use rand::{Rng, SeedableRng, rngs::StdRng};
+
+use crate::{
+    components::{
+        CaptureRing, GameEdge, GameNode, HpLabel, NodeKind, Obstacle, Owner, Packet, Trail,
+    },
+    resources::{
+        Action, AiRng, AiStrategy, AiTimer, ComputerGraph, ControlGroups, Difficulty, EdgeLoad,
+        EdgeMaterials, EventLog, FlowHistory, FlowMap, GameConfig, GameState, GraphEntityMap,
+        InteractionState, KeyBindings, MapSeed, MatchClock, NodePositions, NodeStart,
+        OwnershipHistory, PacketAssets, PacketCounts, PacketPool, Palette, Paused, ReplayMode,
+        Scenario,
+    },
+};
+
+const MAP_FILE: &str = "map.json";
+
+pub fn generate_graph(map_seed: &MapSeed, scenario: Option<&Scenario>) -> ComputerGraph {
+    if let Some(scenario) = scenario {
+        return scenario.graph.clone();
+    }
+
+    match map_seed.0 {
+        Some(seed) => ComputerGraph::from_seed(seed),
+        None => ComputerGraph::random(),
+    }
+}
+
+pub fn pick_start_nodes(computer_graph: &ComputerGraph) -> Option<(NodeIndex, NodeIndex)> {
+    let graph = &computer_graph.graph;
+    let mut best = None;
+    let mut best_dist = -1.0;
+
+    for source in graph.node_indices() {
+        let distances = dijkstra(graph, source, None, |edge| edge.weight().length);
+        for target in graph.node_indices().filter(|&target| target > source) {
+            if let Some(&dist) = distances.get(&target)
+                && dist > best_dist
+            {
+                best = Some((source, target));
+                best_dist = dist;
+            }
+        }
+    }
+
+    best
+}
+
+pub fn spread_start_nodes(computer_graph: &ComputerGraph, count: usize) -> Vec<NodeIndex> {
+    let graph = &computer_graph.graph;
+    let Some((first, second)) = pick_start_nodes(computer_graph) else {
+        return graph.node_indices().take(count).collect();
+    };
+    let mut starts = vec![first, second];
+    let mut nearest_start = vec![f32::INFINITY; graph.node_count()];
+
+    let record_distances = |start: NodeIndex, nearest_start: &mut [f32]| {
+        let distances = dijkstra(graph, start, None, |edge| edge.weight().length);
+        for idx in graph.node_indices() {
+            let dist = distances.get(&idx).copied().unwrap_or(0.0);
+            nearest_start[idx.index()] = nearest_start[idx.index()].min(dist);
+        }
+    };
+    record_distances(first, &mut nearest_start);
+    record_distances(second, &mut nearest_start);
+
+    while starts.len() < count {
+        let Some(next) = graph
+            .node_indices()
+            .filter(|idx| !starts.contains(idx))
+            .max_by(|a, b| {
+                nearest_start[a.index()]
+                    .total_cmp(&nearest_start[b.index()])
+                    .then(b.cmp(a))
+            })
+        else {
+            break;
+        };
+        record_distances(next, &mut nearest_start);
+        starts.push(next);
+    }
+
+    starts
+}
+
+pub fn spawn_map(
+    commands: &mut Commands,
+    meshes: &mut Assets<Mesh>,
+    materials: &mut Assets<ColorMaterial>,
+    entity_map: &mut GraphEntityMap,
+    config: &GameConfig,
+    palette: Palette,
+    computer_graph: ComputerGraph,
+    phase_seed: u64,
+) {
+    let graph = &computer_graph.graph;
+    let mut phase_rng = StdRng::seed_from_u64(phase_seed);
+
+    let has_preset_starts = graph.node_weights().any(|node| node.start.is_some());
+    let starts = if has_preset_starts {
+        Vec::new()
+    } else {
+        spread_start_nodes(&computer_graph, config.faction_count as usize)
+    };
+    if !has_preset_starts && starts.len() < config.faction_count as usize {
+        println!(
+            "Map has room for only {} of {} factions",
+            starts.len(),
+            config.faction_count
+        );
+    }
+
+    let mesh_circle = meshes.add(Circle::new(NodeKind::Normal.radius()));
+    let mesh_fortress = meshes.add(RegularPolygon::new(NodeKind::Fortress.radius(), 6));
+    let mesh_spawner = meshes.add(RegularPolygon::new(NodeKind::Spawner.radius(), 4));
+    let mesh_edge = meshes.add(Rectangle::new(1.0, 0.02));
+
+    for node_idx in graph.node_indices() {
+        let node_data = graph[node_idx];
+
+        let max_hp = config.node_max_hp * node_data.kind.hp_multiplier();
+        let (owner, hp) = if let Some(start) = node_data.start {
+            (start.owner, start.hp)
+        } else if let Some(faction) = starts.iter().position(|&start| start == node_idx) {
+            (Owner::Faction(faction as u8), max_hp)
+        } else {
+            (Owner::Neutral, max_hp * config.neutral_hp_ratio)
+        };
+
+        let color = owner.color(palette);
+        let material = materials.add(ColorMaterial::from(color));
+
+        let entity = commands
+            .spawn((
+                Mesh2d(match node_data.kind {
+                    NodeKind::Normal => mesh_circle.clone(),
+                    NodeKind::Fortress => mesh_fortress.clone(),
+                    NodeKind::Spawner => mesh_spawner.clone(),
+                }),
+                MeshMaterial2d(material),
+                Transform::from_xyz(node_data.position.x, node_data.position.y, 1.0),
+                GameNode::new(node_idx, node_data.kind, owner, hp, config)
+                    .with_fire_phase(phase_rng.random()),
+            ))
+            .id();
+
+        entity_map.nodes.insert(node_idx, entity);
+    }
+
+    let obstacle_color = materials.add(Color::srgb(0.05, 0.05, 0.07));
+    for rect in &computer_graph.obstacles {
+        let center = rect.center();
+        commands.spawn((
+            Mesh2d(meshes.add(Rectangle::from_size(rect.size()))),
+            MeshMaterial2d(obstacle_color.clone()),
+            Transform::from_xyz(center.x, center.y, -0.5),
+            Obstacle,
+        ));
+    }
+
+    let edge_color = materials.add(Color::srgb(0.2, 0.2, 0.2));
+
+    if !cfg!(feature = "gizmo_render") {
+        for edge_idx in graph.edge_indices() {
+            let (u, v) = graph.edge_endpoints(edge_idx).unwrap();
+            let pos_a = graph[u].position;
+            let pos_b = graph[v].position;
+
+            let diff = pos_b - pos_a;
+            let len = graph[edge_idx].length;
+            let pos = (pos_a + pos_b) / 2.0;
+            let angle = diff.y.atan2(diff.x);
+
+            let entity = commands
+                .spawn((
+                    Mesh2d(mesh_edge.clone()),
+                    MeshMaterial2d(edge_color.clone()),
+                    Transform::from_xyz(pos.x, pos.y, 0.0)
+                        .with_rotation(Quat::from_rotation_z(angle))
+                        .with_scale(Vec3::new(len, 1.0, 1.0)),
+                    GameEdge,
+                ))
+                .id();
+
+            entity_map.edges.insert(edge_idx, entity);
+        }
+    }
+
+    commands.insert_resource(NodePositions::from_graph(&computer_graph));
+    commands.insert_resource(computer_graph);
+}
+
+fn phase_seed(map_seed: &MapSeed) -> u64 {
+    map_seed
+        .0
+        .map_or_else(|| rand::rng().random(), |seed| seed.wrapping_add(2))
+}
+
+#[derive(SystemParam)]
+pub struct MatchReset<'w, 's> {
+    commands: Commands<'w, 's>,
+    meshes: ResMut<'w, Assets<Mesh>>,
+    materials: ResMut<'w, Assets<ColorMaterial>>,
+    entity_map: ResMut<'w, GraphEntityMap>,
+    flow_map: ResMut<'w, FlowMap>,
+    flow_history: ResMut<'w, FlowHistory>,
+    interaction: ResMut<'w, InteractionState>,
+    control_groups: ResMut<'w, ControlGroups>,
+    ai_timer: ResMut<'w, AiTimer>,
+    ai_strategy: ResMut<'w, AiStrategy>,
+    ai_rng: ResMut<'w, AiRng>,
+    game_state: ResMut<'w, GameState>,
+    match_clock: ResMut<'w, MatchClock>,
+    ownership_history: ResMut<'w, OwnershipHistory>,
+    packet_pool: ResMut<'w, PacketPool>,
+    edge_load: ResMut<'w, EdgeLoad>,
+    packet_counts: ResMut<'w, PacketCounts>,
+    paused: ResMut<'w, Paused>,
+    difficulty: Res<'w, Difficulty>,
+    map_seed: Res<'w, MapSeed>,
+    config: Res<'w, GameConfig>,
+    palette: Res<'w, Palette>,
+    entities_q: Query<
+        'w,
+        's,
+        Entity,
+        Or<(
+            With<GameNode>,
+            With<Packet>,
+            With<Obstacle>,
+            With<Trail>,
+            With<CaptureRing>,
+            With<HpLabel>,
+        )>,
+    >,
+}
+
+impl MatchReset<'_, '_> {
+    pub fn reset(&mut self, computer_graph: ComputerGraph) {
+        for entity in self.entities_q.iter() {
+            self.commands.entity(entity).despawn();
+        }
+        for &entity in self.entity_map.edges.values() {
+            self.commands.entity(entity).despawn();
+        }
+        for entity in self.packet_pool.0.drain(..) {
+            self.commands.entity(entity).despawn();
+        }
+
+        *self.entity_map = GraphEntityMap::default();
+        *self.flow_map = FlowMap::new(self.config.max_flows_per_node);
+        *self.flow_history = FlowHistory::default();
+        *self.interaction = InteractionState::default();
+        *self.control_groups = ControlGroups::default();
+        *self.ai_timer = AiTimer::new(*self.difficulty);
+        *self.ai_strategy = AiStrategy::default();
+        *self.ai_rng = AiRng::new(&self.map_seed);
+        *self.game_state = GameState::Playing;
+        *self.match_clock = MatchClock::default();
+        *self.ownership_history = OwnershipHistory::default();
+        *self.edge_load = EdgeLoad::default();
+        *self.packet_counts = PacketCounts::default();
+        *self.paused = Paused::default();
+
+        spawn_map(
+            &mut self.commands,
+            &mut self.meshes,
+            &mut self.materials,
+            &mut self.entity_map,
+            &self.config,
+            *self.palette,
+            computer_graph,
+            phase_seed(&self.map_seed),
+        );
+    }
+}
+
+pub fn setup_game(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut entity_map: ResMut<GraphEntityMap>,
+    map_seed: Res<MapSeed>,
+    scenario: Option<Res<Scenario>>,
+    config: Res<GameConfig>,
+    palette: Res<Palette>,
+) {
+    if let Some(scenario) = &scenario {
+        println!("Scenario: {}", scenario.name);
+        if !scenario.description.is_empty() {
+            println!("{}", scenario.description);
+        }
+    }
+
+    commands.insert_resource(EdgeMaterials::new(&mut materials));
+    commands.insert_resource(PacketAssets::new(
+        &mut meshes,
+        &mut materials,
+        &config,
+        *palette,
+    ));
+
+    spawn_map(
+        &mut commands,
+        &mut meshes,
+        &mut materials,
+        &mut entity_map,
+        &config,
+        *palette,
+        generate_graph(&map_seed, scenario.as_deref()),
+        phase_seed(&map_seed),
+    );
+}
+
+pub fn restart_game(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    map_seed: Res<MapSeed>,
+    scenario: Option<Res<Scenario>>,
+    mut event_log: ResMut<EventLog>,
+    mut replay: ResMut<ReplayMode>,
+    mut match_reset: MatchReset,
+) {
+    if !bindings.just_pressed(&keyboard, Action::Restart) {
+        return;
+    }
+
+    *event_log = EventLog::default();
+    *replay = ReplayMode::default();
+    match_reset.reset(generate_graph(&map_seed, scenario.as_deref()));
+}
+
+pub fn save_map(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    graph_res: Res<ComputerGraph>,
+    nodes_q: Query<&GameNode>,
+) {
+    if !bindings.just_pressed(&keyboard, Action::SaveMap) {
+        return;
+    }
+
+    let mut snapshot = graph_res.clone();
+    for node in nodes_q.iter() {
+        snapshot.graph[node.index].start = Some(NodeStart {
+            owner: node.owner,
+            hp: node.hp,
+        });
+    }
+
+    match std::fs::write(MAP_FILE, snapshot.to_json()) {
+        Ok(()) => println!("Saved map to {MAP_FILE}"),
+        Err(err) => println!("Failed to save map to {MAP_FILE}: {err}"),
+    }
+}
+
+pub fn load_map(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    mut event_log: ResMut<EventLog>,
+    mut replay: ResMut<ReplayMode>,
+    mut match_reset: MatchReset,
+) {
+    if !bindings.just_pressed(&keyboard, Action::LoadMap) {
+        return;
+    }
+
+    let computer_graph = match std::fs::read_to_string(MAP_FILE) {
+        Ok(json) => match ComputerGraph::from_json(&json) {
+            Ok(computer_graph) => computer_graph,
+            Err(err) => {
+                println!("Failed to load {MAP_FILE}: {err}");
+                return;
+            }
+        },
+        Err(err) => {
+            println!("Failed to read {MAP_FILE}: {err}");
+            return;
+        }
+    };
+
+    println!("Loaded map from {MAP_FILE}");
+    *event_log = EventLog::default();
+    *replay = ReplayMode::default();
+    match_reset.reset(computer_graph);
+}
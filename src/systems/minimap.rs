@@ -0,0 +1,78 @@
+use bevy::{
+    camera::Camera,
+    color::{Alpha, Color},
+    ecs::system::{Query, Res},
+    gizmos::gizmos::Gizmos,
+    math::{Rect, Vec2},
+    transform::components::GlobalTransform,
+};
+
+use crate::{
+    components::GameNode,
+    resources::{ComputerGraph, FogOfWar, NodePositions, Palette},
+};
+
+const MINIMAP_SIZE: f32 = 180.0;
+const MINIMAP_MARGIN: f32 = 12.0;
+
+pub fn draw_minimap(
+    mut gizmos: Gizmos,
+    camera_q: Query<(&Camera, &GlobalTransform)>,
+    nodes_q: Query<&GameNode>,
+    graph_res: Res<ComputerGraph>,
+    positions: Res<NodePositions>,
+    palette: Res<Palette>,
+    fog: Res<FogOfWar>,
+) {
+    let Ok((camera, cam_transform)) = camera_q.single() else {
+        return;
+    };
+    let Some(viewport) = camera.logical_viewport_size() else {
+        return;
+    };
+
+    let screen_min = viewport - Vec2::splat(MINIMAP_MARGIN + MINIMAP_SIZE);
+    let screen_max = viewport - Vec2::splat(MINIMAP_MARGIN);
+    let (Ok(corner_a), Ok(corner_b)) = (
+        camera.viewport_to_world_2d(cam_transform, screen_min),
+        camera.viewport_to_world_2d(cam_transform, screen_max),
+    ) else {
+        return;
+    };
+    let frame = Rect::from_corners(corner_a, corner_b);
+
+    let mut bounds = Rect::EMPTY;
+    for &pos in positions.0.values() {
+        bounds = bounds.union_point(pos);
+    }
+    if bounds.is_empty() {
+        return;
+    }
+
+    let inner = frame.inflate(-frame.width() * 0.08);
+    let scale = (inner.size() / bounds.size().max(Vec2::splat(f32::EPSILON))).min_element();
+    let to_minimap = |pos: Vec2| inner.center() + (pos - bounds.center()) * scale;
+
+    gizmos.rect_2d(frame.center(), frame.size(), Color::srgb(0.4, 0.4, 0.4));
+
+    for edge_idx in graph_res.graph.edge_indices() {
+        let Some((u, v)) = graph_res.graph.edge_endpoints(edge_idx) else {
+            continue;
+        };
+        gizmos.line_2d(
+            to_minimap(positions.0[&u]),
+            to_minimap(positions.0[&v]),
+            Color::srgb(0.2, 0.2, 0.2).with_alpha(0.6),
+        );
+    }
+
+    let dot_radius = frame.width() * 0.015;
+    for node in nodes_q.iter() {
+        let color = if fog.is_visible(node.index) {
+            node.owner.color(*palette)
+        } else {
+            FogOfWar::HIDDEN_COLOR
+        };
+        gizmos.circle_2d(to_minimap(positions.0[&node.index]), dot_radius, color);
+    }
+}
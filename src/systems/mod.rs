@@ -1,4 +1,27 @@
 pub mod ai;
+#[cfg(feature = "audio")]
+pub mod audio;
+pub mod bot;
+pub mod camera;
+pub mod chart;
+pub mod debug;
+pub mod fog;
+pub mod fx;
+#[cfg(feature = "gizmo_render")]
+pub mod gizmo_render;
+pub mod headless;
+pub mod help;
+pub mod hud;
 pub mod interaction;
+pub mod labels;
+pub mod map;
+pub mod minimap;
 pub mod packet;
+pub mod pause;
+pub mod regen;
+pub mod replay;
+pub mod sandbox;
+pub mod snapshot;
+pub mod tooltip;
+pub mod victory;
 pub mod visual;
@@ -1,23 +1,30 @@
 use bevy::{
     asset::Assets,
-    color::Color,
+    camera::visibility::Visibility,
     ecs::{
+        change_detection::DetectChanges,
         entity::Entity,
+        message::MessageWriter,
         system::{Commands, Query, Res, ResMut},
     },
-    math::primitives::Circle,
+    math::{Vec2, Vec3, primitives::Circle},
     mesh::{Mesh, Mesh2d},
     platform::collections::{HashMap, HashSet},
-    sprite_render::{ColorMaterial, MeshMaterial2d},
-    time::Time,
+    sprite_render::MeshMaterial2d,
+    time::{Fixed, Time, Timer, TimerMode},
     transform::components::Transform,
 };
 use petgraph::graph::NodeIndex;
+use std::time::Duration;
 
 use crate::{
-    NODE_MAX_HP, PACKET_POWER, PACKET_SPEED, SPAWN_INTERVAL,
-    components::{GameNode, Owner, Packet},
-    resources::{ComputerGraph, FlowMap, GraphEntityMap},
+    components::{GameNode, NodeKind, Owner, Packet, Trail},
+    events::{CaptureEvent, FireEvent},
+    resources::{
+        ComputerGraph, EdgeLoad, FlowMap, FogOfWar, FullHpFlow, GameConfig, GameState,
+        GraphEntityMap, NodePositions, NodeSnapshot, PacketAssets, PacketCounts, PacketPool,
+        Paused, ReplayMode, ShowTrails,
+    },
 };
 
 pub fn spawn_packets(
@@ -25,117 +32,689 @@ pub fn spawn_packets(
     time: Res<Time>,
     mut nodes_q: Query<(&mut GameNode, &Transform)>,
     graph_res: Res<ComputerGraph>,
-    mut materials: ResMut<Assets<ColorMaterial>>,
-    mut meshes: ResMut<Assets<Mesh>>,
-    flow_map: Res<FlowMap>,
+    positions: Res<NodePositions>,
+    packet_assets: Res<PacketAssets>,
+    mut packet_pool: ResMut<PacketPool>,
+    mut flow_map: ResMut<FlowMap>,
+    config: Res<GameConfig>,
+    game_state: Res<GameState>,
+    paused: Res<Paused>,
+    snapshot: Res<NodeSnapshot>,
+    mut edge_load: ResMut<EdgeLoad>,
+    mut packet_counts: ResMut<PacketCounts>,
+    mut fires: MessageWriter<FireEvent>,
 ) {
-    let node_states: HashMap<NodeIndex, (Owner, f32)> = nodes_q
-        .iter()
-        .map(|(n, _)| (n.index, (n.owner, n.hp)))
-        .collect();
-
-    let packet_mesh = meshes.add(Circle::new(0.015));
+    if *game_state != GameState::Playing || paused.0 {
+        return;
+    }
 
     for (mut node, transform) in nodes_q.iter_mut() {
-        let mut active_targets = HashSet::new();
+        node.buff_timer.tick(time.delta());
+        node.capture_cooldown.tick(time.delta());
 
-        if node.owner == Owner::Player {
-            if let Some(targets) = flow_map.flows.get(&node.index) {
-                for &t in targets {
-                    active_targets.insert(t);
-                }
-            }
-        } else if node.owner == Owner::Enemy {
-            for neighbor_idx in graph_res.0.neighbors(node.index) {
-                if let Some((neighbor_owner, neighbor_hp)) = node_states.get(&neighbor_idx) {
-                    if *neighbor_owner != Owner::Enemy {
-                        active_targets.insert(neighbor_idx);
-                    } else if *neighbor_hp < NODE_MAX_HP {
-                        active_targets.insert(neighbor_idx);
-                    }
+        let mut active_targets = active_targets(&node, &flow_map);
+        if node.owner == Owner::PLAYER && config.full_hp_flow != FullHpFlow::Keep {
+            let idle: Vec<NodeIndex> = active_targets
+                .iter()
+                .copied()
+                .filter(|&target| is_idle_flow(target, &snapshot, &flow_map, &config))
+                .collect();
+            for target in idle {
+                active_targets.remove(&target);
+                if config.full_hp_flow == FullHpFlow::Remove {
+                    flow_map.remove_route(&[node.index, target]);
                 }
             }
         }
 
-        node.timer.tick(time.delta());
+        node.target_timers
+            .retain(|target, _| active_targets.contains(target));
+
+        if active_targets.is_empty() {
+            continue;
+        }
 
-        if node.timer.just_finished() && !active_targets.is_empty() && node.owner != Owner::Neutral
-        {
-            let target_count = active_targets.len();
-            let cooldown_mult = target_count as f32;
+        let (source, owner) = (node.index, node.owner);
+        let weight = |target: NodeIndex| {
+            if owner == Owner::PLAYER {
+                flow_map.weight(source, target)
+            } else {
+                1.0
+            }
+        };
+        let total_weight: f32 = active_targets.iter().map(|&t| weight(t)).sum();
+        let firing_multiplier = node.firing_multiplier();
+        let phase = node.fire_phase;
+        let mut ready_targets = Vec::new();
 
-            node.timer.set_duration(std::time::Duration::from_secs_f32(
-                SPAWN_INTERVAL * cooldown_mult,
-            ));
-            node.timer.reset();
+        for (slot, &target_idx) in active_targets.iter().enumerate() {
+            let interval = Duration::from_secs_f32(
+                config.spawn_interval * total_weight / (weight(target_idx) * firing_multiplier),
+            );
+            let timer = node.target_timers.entry(target_idx).or_insert_with(|| {
+                let mut timer = Timer::new(interval, TimerMode::Repeating);
+                timer.set_elapsed(
+                    interval.mul_f32((slot as f32 / active_targets.len() as f32 + phase).fract()),
+                );
+                timer
+            });
 
-            for &target_idx in &active_targets {
-                let target_pos = graph_res.0[target_idx].position;
-                let dist = transform.translation.truncate().distance(target_pos);
+            if timer.duration() != interval {
+                timer.set_duration(interval);
+            }
 
-                let color = match node.owner {
-                    Owner::Player => Color::srgb(0.5, 0.5, 1.0),
-                    Owner::Enemy => Color::srgb(1.0, 0.5, 0.5),
-                    _ => Color::WHITE,
-                };
+            timer.tick(time.delta());
+            if timer.just_finished() {
+                ready_targets.push(target_idx);
+            }
+        }
 
-                commands.spawn((
-                    Mesh2d(packet_mesh.clone()),
-                    MeshMaterial2d(materials.add(ColorMaterial::from(color))),
-                    Transform::from_translation(transform.translation),
-                    Packet {
-                        from: node.index,
-                        to: target_idx,
-                        owner: node.owner,
-                        progress: 0.0,
-                        edge_len: dist,
-                    },
-                ));
+        if node.kind == NodeKind::Spawner {
+            node.spawner_timer.tick(time.delta());
+            let attacked = active_targets
+                .iter()
+                .copied()
+                .filter(|&target| snapshot.owner(target) != Some(node.owner))
+                .filter(|target| !ready_targets.contains(target))
+                .min();
+            if node.spawner_timer.just_finished()
+                && let Some(target_idx) = attacked
+                && !packet_counts.at_cap(node.owner, config.max_packets_per_owner)
+                && edge_load.try_reserve(
+                    &graph_res,
+                    node.index,
+                    target_idx,
+                    config.max_packets_per_edge,
+                )
+            {
+                packet_counts.add(node.owner);
+                fires.write(FireEvent { node: node.index });
+                spawn_packet(
+                    &mut commands,
+                    &mut packet_pool,
+                    &packet_assets,
+                    transform.translation,
+                    Packet::new(
+                        node.index,
+                        target_idx,
+                        node.owner,
+                        node.kind,
+                        edge_length(&graph_res, &positions, node.index, target_idx),
+                        &config,
+                    ),
+                );
+            }
+        }
+
+        for &target_idx in &ready_targets {
+            if node.energy < config.packet_energy_cost
+                || packet_counts.at_cap(node.owner, config.max_packets_per_owner)
+            {
+                break;
             }
+            if !edge_load.try_reserve(
+                &graph_res,
+                node.index,
+                target_idx,
+                config.max_packets_per_edge,
+            ) {
+                continue;
+            }
+            packet_counts.add(node.owner);
+            node.energy -= config.packet_energy_cost;
+            fires.write(FireEvent { node: node.index });
+
+            spawn_packet(
+                &mut commands,
+                &mut packet_pool,
+                &packet_assets,
+                transform.translation,
+                Packet::new(
+                    node.index,
+                    target_idx,
+                    node.owner,
+                    node.kind,
+                    edge_length(&graph_res, &positions, node.index, target_idx),
+                    &config,
+                ),
+            );
         }
     }
 }
 
+pub fn edge_length(
+    graph_res: &ComputerGraph,
+    positions: &NodePositions,
+    from: NodeIndex,
+    to: NodeIndex,
+) -> f32 {
+    graph_res
+        .graph
+        .find_edge(from, to)
+        .map(|edge_idx| graph_res.graph[edge_idx].length)
+        .unwrap_or_else(|| positions.0[&from].distance(positions.0[&to]))
+}
+
+fn active_targets(node: &GameNode, flow_map: &FlowMap) -> HashSet<NodeIndex> {
+    match node.owner {
+        Owner::PLAYER => flow_map.flows.get(&node.index).cloned().unwrap_or_default(),
+        owner if owner.is_ai() => node.targets.clone(),
+        _ => HashSet::new(),
+    }
+}
+
+fn is_idle_flow(
+    target: NodeIndex,
+    snapshot: &NodeSnapshot,
+    flow_map: &FlowMap,
+    config: &GameConfig,
+) -> bool {
+    let forwards = config.forward_surplus_packets && flow_map.flows.contains_key(&target);
+    !forwards
+        && snapshot
+            .0
+            .get(&target)
+            .is_some_and(|&(owner, hp, max_hp)| owner == Owner::PLAYER && hp >= max_hp)
+}
+
+pub fn spawn_packet(
+    commands: &mut Commands,
+    packet_pool: &mut PacketPool,
+    packet_assets: &PacketAssets,
+    translation: Vec3,
+    packet: Packet,
+) {
+    let material = MeshMaterial2d(packet_assets.material(packet.owner));
+    let entity = packet_pool
+        .0
+        .pop()
+        .unwrap_or_else(|| commands.spawn_empty().id());
+    let mut entity_commands = commands.entity(entity);
+
+    entity_commands.insert((
+        Transform::from_translation(translation),
+        Visibility::Inherited,
+        packet,
+    ));
+    if !cfg!(feature = "gizmo_render") {
+        entity_commands.insert((Mesh2d(packet_assets.mesh.clone()), material));
+    }
+}
+
 pub fn move_packets(
     mut commands: Commands,
     time: Res<Time>,
     mut packets_q: Query<(Entity, &mut Packet, &mut Transform)>,
     mut nodes_q: Query<&mut GameNode>,
     graph_res: Res<ComputerGraph>,
+    positions: Res<NodePositions>,
     entity_map: Res<GraphEntityMap>,
+    mut flow_map: ResMut<FlowMap>,
+    mut packet_pool: ResMut<PacketPool>,
+    config: Res<GameConfig>,
+    paused: Res<Paused>,
+    replay: Res<ReplayMode>,
+    mut captures: MessageWriter<CaptureEvent>,
+    packet_assets: Res<PacketAssets>,
+    (show_trails, fog): (Res<ShowTrails>, Res<FogOfWar>),
+    (mut edge_load, mut packet_counts): (ResMut<EdgeLoad>, ResMut<PacketCounts>),
 ) {
+    const TRAILS_PER_EDGE: f32 = 6.0;
+    const TRAIL_LIFE: f32 = 0.2;
+
+    if paused.0 {
+        return;
+    }
+
+    edge_load.0.clear();
+    packet_counts.0.clear();
+
     for (packet_entity, mut packet, mut transform) in packets_q.iter_mut() {
-        let speed = PACKET_SPEED / packet.edge_len;
+        let speed = packet.travel_speed(&config);
+        let previous_progress = packet.progress;
         packet.progress += speed * time.delta_secs();
+        let overshoot = (packet.progress - 1.0).max(0.0) * packet.edge_len;
+        packet.progress = packet.progress.min(1.0);
 
-        let start_pos = graph_res.0[packet.from].position;
-        let end_pos = graph_res.0[packet.to].position;
-
-        let current_pos = start_pos.lerp(end_pos, packet.progress);
+        let current_pos = packet_position(&positions, &packet, packet.progress);
         transform.translation.x = current_pos.x;
         transform.translation.y = current_pos.y;
 
-        if packet.progress >= 1.0 {
-            commands.entity(packet_entity).despawn();
+        if packet.progress < 1.0 {
+            let crossed_step = (previous_progress * TRAILS_PER_EDGE).floor()
+                < (packet.progress * TRAILS_PER_EDGE).floor();
+            if show_trails.0
+                && !cfg!(feature = "gizmo_render")
+                && crossed_step
+                && (fog.is_visible(packet.from) || fog.is_visible(packet.to))
+            {
+                commands.spawn((
+                    Mesh2d(packet_assets.mesh.clone()),
+                    MeshMaterial2d(packet_assets.trail_material(packet.owner)),
+                    Transform::from_xyz(current_pos.x, current_pos.y, 0.5),
+                    Trail {
+                        life: Timer::from_seconds(TRAIL_LIFE, TimerMode::Once),
+                    },
+                ));
+            }
+            edge_load.add(&graph_res, packet.from, packet.to);
+            packet_counts.add(packet.owner);
+            continue;
+        }
+
+        if replay.active {
+            recycle_packet(&mut commands, &mut packet_pool, packet_entity);
+            continue;
+        }
+
+        let Some(mut target_node) = entity_map
+            .nodes
+            .get(&packet.to)
+            .and_then(|&entity| nodes_q.get_mut(entity).ok())
+        else {
+            recycle_packet(&mut commands, &mut packet_pool, packet_entity);
+            continue;
+        };
 
-            if let Some(&target_entity) = entity_map.nodes.get(&packet.to) {
-                if let Ok(mut target_node) = nodes_q.get_mut(target_entity) {
-                    process_hit(&mut target_node, packet.owner);
-                }
+        if target_node.owner == packet.owner && target_node.hp >= target_node.max_hp {
+            let next_hop = match packet.owner {
+                Owner::PLAYER => flow_map
+                    .flows
+                    .get(&packet.to)
+                    .and_then(|targets| targets.iter().min().copied()),
+                _ => target_node.targets.iter().min().copied(),
+            };
+
+            if config.forward_surplus_packets
+                && let Some(next) = next_hop
+                && let Some(edge_idx) = graph_res.graph.find_edge(packet.to, next)
+            {
+                packet.from = packet.to;
+                packet.to = next;
+                packet.edge_len = graph_res.graph[edge_idx].length;
+                packet.progress = (overshoot / packet.edge_len).min(1.0);
+                edge_load.add(&graph_res, packet.from, packet.to);
+                packet_counts.add(packet.owner);
+            } else {
+                recycle_packet(&mut commands, &mut packet_pool, packet_entity);
+            }
+            continue;
+        }
+
+        recycle_packet(&mut commands, &mut packet_pool, packet_entity);
+
+        let previous_owner = target_node.owner;
+        if process_hit(&mut target_node, &packet, &config) {
+            captures.write(CaptureEvent {
+                node: packet.to,
+                previous_owner,
+                new_owner: packet.owner,
+            });
+            if previous_owner == Owner::PLAYER {
+                flow_map.flows.remove(&packet.to);
+            }
+            if packet.owner == Owner::PLAYER {
+                flow_map.advance(packet.to);
             }
         }
     }
 }
 
-fn process_hit(node: &mut GameNode, packet_owner: Owner) {
-    if node.owner == packet_owner {
-        node.hp = (node.hp + PACKET_POWER).min(NODE_MAX_HP);
+pub fn resolve_packet_collisions(
+    mut commands: Commands,
+    packets_q: Query<(Entity, &Packet)>,
+    mut packet_pool: ResMut<PacketPool>,
+) {
+    const COLLISION_EPSILON: f32 = 0.01;
+
+    let mut edges: HashMap<(NodeIndex, NodeIndex), Vec<(Entity, Owner, bool, f32)>> =
+        HashMap::new();
+
+    for (entity, packet) in packets_q.iter() {
+        let forward = packet.from < packet.to;
+        let key = (packet.from.min(packet.to), packet.from.max(packet.to));
+        let position = if forward {
+            packet.progress
+        } else {
+            1.0 - packet.progress
+        };
+        edges
+            .entry(key)
+            .or_default()
+            .push((entity, packet.owner, forward, position));
+    }
+
+    for packets in edges.values() {
+        let mut forward: Vec<_> = packets.iter().filter(|p| p.2).collect();
+        let mut backward: Vec<_> = packets.iter().filter(|p| !p.2).collect();
+        if forward.is_empty() || backward.is_empty() {
+            continue;
+        }
+
+        forward.sort_by(|a, b| b.3.total_cmp(&a.3));
+        backward.sort_by(|a, b| a.3.total_cmp(&b.3));
+
+        let mut cancelled = HashSet::new();
+        for &&(fwd_entity, fwd_owner, _, fwd_pos) in &forward {
+            let Some(&&(back_entity, _, _, back_pos)) = backward
+                .iter()
+                .find(|p| p.1 != fwd_owner && !cancelled.contains(&p.0))
+            else {
+                continue;
+            };
+
+            if fwd_pos + COLLISION_EPSILON >= back_pos {
+                cancelled.insert(fwd_entity);
+                cancelled.insert(back_entity);
+                recycle_packet(&mut commands, &mut packet_pool, fwd_entity);
+                recycle_packet(&mut commands, &mut packet_pool, back_entity);
+            }
+        }
+    }
+}
+
+fn packet_position(positions: &NodePositions, packet: &Packet, progress: f32) -> Vec2 {
+    const LANE_OFFSET: f32 = 0.012;
+
+    let start = positions.0[&packet.from];
+    let end = positions.0[&packet.to];
+    let (edge, lane) = if packet.from < packet.to {
+        (end - start, LANE_OFFSET)
+    } else {
+        (start - end, -LANE_OFFSET)
+    };
+    start.lerp(end, progress) + edge.normalize_or_zero().perp() * lane
+}
+
+fn recycle_packet(commands: &mut Commands, packet_pool: &mut PacketPool, entity: Entity) {
+    commands
+        .entity(entity)
+        .remove::<Packet>()
+        .insert(Visibility::Hidden);
+    packet_pool.0.push(entity);
+}
+
+pub fn process_hit(node: &mut GameNode, packet: &Packet, config: &GameConfig) -> bool {
+    if node.owner == packet.owner {
+        node.hp = (node.hp + packet.power).min(node.max_hp);
     } else {
-        node.hp -= PACKET_POWER;
-        if node.hp <= 0.0 {
-            node.owner = packet_owner;
-            node.hp = 10.0;
+        node.hp -= packet.power * node.kind.damage_multiplier();
+        node.last_hit.reset();
+        if node.hp <= 0.0 && node.capture_cooldown.remaining_secs() > 0.0 {
+            node.hp = 0.0;
+        } else if node.hp <= 0.0 {
+            node.begin_color_transition();
+            node.owner = packet.owner;
+            node.hp = config.capture_hp.resolve(node.max_hp);
+            node.capture_cooldown = Timer::from_seconds(config.capture_cooldown, TimerMode::Once);
             node.targets.clear();
+            node.buff_timer = Timer::from_seconds(config.capture_buff_duration, TimerMode::Once);
+            node.resistance_timer = Timer::from_seconds(config.resistance_window, TimerMode::Once);
+            return true;
+        }
+    }
+    false
+}
+
+pub fn sync_packet_mesh(
+    config: Res<GameConfig>,
+    packet_assets: Res<PacketAssets>,
+    mut meshes: ResMut<Assets<Mesh>>,
+) {
+    if config.is_changed()
+        && let Some(mesh) = meshes.get_mut(&packet_assets.mesh)
+    {
+        *mesh = Circle::new(config.packet_radius).into();
+    }
+}
+
+pub fn interpolate_packets(
+    fixed_time: Res<Time<Fixed>>,
+    mut packets_q: Query<(&Packet, &mut Transform)>,
+    positions: Res<NodePositions>,
+    config: Res<GameConfig>,
+    paused: Res<Paused>,
+) {
+    if paused.0 {
+        return;
+    }
+
+    let overstep = fixed_time.overstep().as_secs_f32();
+    for (packet, mut transform) in packets_q.iter_mut() {
+        let speed = packet.travel_speed(&config);
+        let progress = (packet.progress + speed * overstep).min(1.0);
+        let pos = packet_position(&positions, packet, progress);
+        transform.translation.x = pos.x;
+        transform.translation.y = pos.y;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        PACKET_POWER,
+        resources::{CaptureHp, Palette},
+    };
+    use bevy::{
+        ecs::{message::Messages, system::RunSystemOnce, world::World},
+        sprite_render::ColorMaterial,
+    };
+
+    fn node(owner: Owner, hp: f32, config: &GameConfig) -> GameNode {
+        GameNode::new(NodeIndex::new(0), NodeKind::Normal, owner, hp, config)
+    }
+
+    fn packet(owner: Owner, config: &GameConfig) -> Packet {
+        Packet::new(
+            NodeIndex::new(1),
+            NodeIndex::new(0),
+            owner,
+            NodeKind::Normal,
+            1.0,
+            config,
+        )
+    }
+
+    fn test_world(
+        graph: ComputerGraph,
+        flow_map: FlowMap,
+        snapshot: NodeSnapshot,
+        config: GameConfig,
+        elapsed: f32,
+    ) -> World {
+        let mut world = World::new();
+        let mut meshes = Assets::<Mesh>::default();
+        let mut materials = Assets::<ColorMaterial>::default();
+        let mut time = Time::<()>::default();
+        time.advance_by(Duration::from_secs_f32(elapsed));
+
+        world.insert_resource(PacketAssets::new(
+            &mut meshes,
+            &mut materials,
+            &config,
+            Palette::default(),
+        ));
+        world.insert_resource(NodePositions::from_graph(&graph));
+        world.insert_resource(graph);
+        world.insert_resource(time);
+        world.insert_resource(flow_map);
+        world.insert_resource(snapshot);
+        world.insert_resource(config);
+        world.init_resource::<PacketPool>();
+        world.init_resource::<EdgeLoad>();
+        world.init_resource::<PacketCounts>();
+        world.init_resource::<Messages<FireEvent>>();
+        world.init_resource::<GameState>();
+        world.init_resource::<Paused>();
+        world
+    }
+
+    #[test]
+    fn enemy_packet_damages_node() {
+        let config = GameConfig::default();
+        let mut target = node(Owner::Neutral, 50.0, &config);
+
+        assert!(!process_hit(
+            &mut target,
+            &packet(Owner::PLAYER, &config),
+            &config
+        ));
+        assert_eq!(target.hp, 50.0 - config.packet_power);
+        assert_eq!(target.owner, Owner::Neutral);
+    }
+
+    #[test]
+    fn lethal_hit_captures_node() {
+        let config = GameConfig::default();
+        let mut target = node(Owner::Faction(1), config.packet_power, &config);
+        target.targets.insert(NodeIndex::new(1));
+
+        assert!(process_hit(
+            &mut target,
+            &packet(Owner::PLAYER, &config),
+            &config
+        ));
+        assert_eq!(target.owner, Owner::PLAYER);
+        assert_eq!(target.hp, 10.0);
+        assert!(target.targets.is_empty());
+    }
+
+    #[test]
+    fn friendly_packet_heals_up_to_max() {
+        let config = GameConfig::default();
+        let mut target = node(Owner::PLAYER, 50.0, &config);
+
+        assert!(!process_hit(
+            &mut target,
+            &packet(Owner::PLAYER, &config),
+            &config
+        ));
+        assert_eq!(target.hp, 50.0 + config.packet_power);
+
+        target.hp = target.max_hp;
+        process_hit(&mut target, &packet(Owner::PLAYER, &config), &config);
+        assert_eq!(target.hp, target.max_hp);
+    }
+
+    #[test]
+    fn node_never_fires_twice_at_one_target_per_tick() {
+        let config = GameConfig::default();
+        let graph = ComputerGraph::from_seed(3);
+        let source = NodeIndex::new(0);
+        let neighbors: Vec<NodeIndex> = graph.graph.neighbors(source).collect();
+
+        let mut flow_map = FlowMap::default();
+        for &target in &neighbors {
+            flow_map.add_route(&[source, target], |idx| idx == source);
         }
+        let mut spawner = GameNode::new(source, NodeKind::Spawner, Owner::PLAYER, 50.0, &config);
+        spawner.targets.extend(neighbors.iter().copied());
+        let elapsed = config.spawner_interval * 2.0;
+
+        let mut world = test_world(graph, flow_map, NodeSnapshot::default(), config, elapsed);
+        world.spawn((spawner, Transform::default()));
+
+        world.run_system_once(spawn_packets).unwrap();
+
+        let mut fired = HashSet::new();
+        for packet in world.query::<&Packet>().iter(&world) {
+            assert!(
+                fired.insert(packet.to),
+                "duplicate packet to {:?}",
+                packet.to
+            );
+        }
+        assert_eq!(fired.len(), neighbors.len());
+    }
+
+    #[test]
+    fn full_hp_friendly_flow_is_removed() {
+        let config = GameConfig {
+            full_hp_flow: FullHpFlow::Remove,
+            ..GameConfig::default()
+        };
+        let graph = ComputerGraph::from_seed(3);
+        let source = NodeIndex::new(0);
+        let mut neighbors = graph.graph.neighbors(source);
+        let (healed, attacked) = (neighbors.next().unwrap(), neighbors.next().unwrap());
+
+        let mut flow_map = FlowMap::default();
+        flow_map.add_route(&[source, healed], |idx| idx == source);
+        flow_map.add_route(&[source, attacked], |idx| idx == source);
+        let mut snapshot = NodeSnapshot::default();
+        snapshot.0.insert(healed, (Owner::PLAYER, 100.0, 100.0));
+        snapshot.0.insert(attacked, (Owner::Neutral, 50.0, 100.0));
+        let elapsed = config.spawn_interval * 2.0;
+
+        let mut world = test_world(graph, flow_map, snapshot, config, elapsed);
+        world.spawn((
+            node(Owner::PLAYER, 50.0, &GameConfig::default()),
+            Transform::default(),
+        ));
+
+        world.run_system_once(spawn_packets).unwrap();
+
+        let targets: Vec<NodeIndex> = world
+            .query::<&Packet>()
+            .iter(&world)
+            .map(|packet| packet.to)
+            .collect();
+        assert_eq!(targets, vec![attacked]);
+        let flows = &world.resource::<FlowMap>().flows[&source];
+        assert!(!flows.contains(&healed));
+        assert!(flows.contains(&attacked));
+    }
+
+    #[test]
+    fn capture_cooldown_blocks_instant_recapture() {
+        let config = GameConfig {
+            capture_hp: CaptureHp::Flat(PACKET_POWER),
+            ..GameConfig::default()
+        };
+        let mut target = node(Owner::Faction(1), config.packet_power, &config);
+
+        assert!(process_hit(
+            &mut target,
+            &packet(Owner::PLAYER, &config),
+            &config
+        ));
+        assert!(!process_hit(
+            &mut target,
+            &packet(Owner::Faction(1), &config),
+            &config
+        ));
+        assert_eq!(target.owner, Owner::PLAYER);
+
+        target
+            .capture_cooldown
+            .tick(Duration::from_secs_f32(config.capture_cooldown));
+        assert!(process_hit(
+            &mut target,
+            &packet(Owner::Faction(1), &config),
+            &config
+        ));
+        assert_eq!(target.owner, Owner::Faction(1));
+    }
+
+    #[test]
+    fn stale_packet_from_previous_owner_damages_captured_node() {
+        let config = GameConfig::default();
+        let mut target = node(Owner::Faction(1), config.packet_power, &config);
+
+        assert!(process_hit(
+            &mut target,
+            &packet(Owner::PLAYER, &config),
+            &config
+        ));
+        let captured_hp = target.hp;
+
+        assert!(!process_hit(
+            &mut target,
+            &packet(Owner::Faction(1), &config),
+            &config
+        ));
+        assert_eq!(target.owner, Owner::PLAYER);
+        assert_eq!(target.hp, captured_hp - config.packet_power);
     }
 }
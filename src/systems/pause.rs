@@ -0,0 +1,84 @@
+use bevy::{
+    color::Color,
+    ecs::{
+        change_detection::DetectChanges,
+        entity::Entity,
+        query::With,
+        system::{Commands, Query, Res, ResMut},
+    },
+    input::{ButtonInput, keyboard::KeyCode},
+    text::{TextColor, TextFont},
+    time::{Time, Virtual},
+    ui::{GlobalZIndex, Node, PositionType, Val, widget::Text},
+};
+
+use crate::{
+    components::PausedUi,
+    resources::{Action, KeyBindings, Paused, TimeScale},
+};
+
+const MIN_TIME_SCALE: f32 = 0.125;
+const MAX_TIME_SCALE: f32 = 8.0;
+
+pub fn toggle_pause(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    mut paused: ResMut<Paused>,
+) {
+    if bindings.just_pressed(&keyboard, Action::Pause) {
+        paused.0 = !paused.0;
+    }
+}
+
+pub fn change_time_scale(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    mut time_scale: ResMut<TimeScale>,
+    mut virtual_time: ResMut<Time<Virtual>>,
+) {
+    let factor = if bindings.just_pressed(&keyboard, Action::SlowDown) {
+        0.5
+    } else if bindings.just_pressed(&keyboard, Action::SpeedUp) {
+        2.0
+    } else {
+        return;
+    };
+
+    time_scale.0 = (time_scale.0 * factor).clamp(MIN_TIME_SCALE, MAX_TIME_SCALE);
+    virtual_time.set_relative_speed(time_scale.0);
+}
+
+pub fn show_paused_ui(
+    mut commands: Commands,
+    paused: Res<Paused>,
+    indicator_q: Query<Entity, With<PausedUi>>,
+) {
+    if !paused.is_changed() {
+        return;
+    }
+
+    for entity in indicator_q.iter() {
+        commands.entity(entity).despawn();
+    }
+
+    if !paused.0 {
+        return;
+    }
+
+    commands.spawn((
+        Text::new("PAUSED"),
+        TextFont {
+            font_size: 32.0,
+            ..TextFont::default()
+        },
+        TextColor(Color::srgb(10.0, 10.0, 0.0)),
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(12.0),
+            left: Val::Px(12.0),
+            ..Node::default()
+        },
+        GlobalZIndex(100),
+        PausedUi,
+    ));
+}
@@ -0,0 +1,146 @@
+use bevy::{
+    ecs::{
+        message::MessageWriter,
+        system::{Query, Res, ResMut},
+    },
+    time::Time,
+};
+use petgraph::graph::NodeIndex;
+
+use crate::{
+    components::{GameNode, Owner},
+    events::CaptureEvent,
+    resources::{ComputerGraph, FlowMap, GameConfig, GameState, NodeSnapshot, Paused, RegenTimer},
+};
+
+pub fn regen_neutral_nodes(
+    mut nodes_q: Query<&mut GameNode>,
+    time: Res<Time>,
+    mut regen_timer: ResMut<RegenTimer>,
+    config: Res<GameConfig>,
+    game_state: Res<GameState>,
+    paused: Res<Paused>,
+) {
+    if *game_state != GameState::Playing || paused.0 || config.neutral_regen_rate <= 0.0 {
+        return;
+    }
+
+    regen_timer.0.tick(time.delta());
+    let ticks = regen_timer.0.times_finished_this_tick();
+    if ticks == 0 {
+        return;
+    }
+
+    let amount = config.neutral_regen_rate * regen_timer.0.duration().as_secs_f32() * ticks as f32;
+
+    for mut node in nodes_q.iter_mut() {
+        let neutral_hp = node.max_hp * config.neutral_hp_ratio;
+        if node.owner == Owner::Neutral && node.hp < neutral_hp {
+            node.hp = (node.hp + amount).min(neutral_hp);
+        }
+    }
+}
+
+pub fn passive_regen(
+    mut nodes_q: Query<&mut GameNode>,
+    time: Res<Time>,
+    config: Res<GameConfig>,
+    game_state: Res<GameState>,
+    paused: Res<Paused>,
+) {
+    if *game_state != GameState::Playing || paused.0 {
+        return;
+    }
+
+    for mut node in nodes_q.iter_mut() {
+        node.last_hit.tick(time.delta());
+
+        if node.owner == Owner::Neutral || !node.last_hit.is_finished() {
+            continue;
+        }
+
+        if node.hp < node.max_hp {
+            node.hp = (node.hp + config.passive_regen_rate * time.delta_secs()).min(node.max_hp);
+        }
+    }
+}
+
+pub fn regen_energy(
+    mut nodes_q: Query<&mut GameNode>,
+    time: Res<Time>,
+    config: Res<GameConfig>,
+    game_state: Res<GameState>,
+    paused: Res<Paused>,
+) {
+    if *game_state != GameState::Playing || paused.0 {
+        return;
+    }
+
+    for mut node in nodes_q.iter_mut() {
+        if node.owner == Owner::Neutral || node.energy >= config.max_energy {
+            continue;
+        }
+
+        node.energy =
+            (node.energy + config.energy_regen_rate * time.delta_secs()).min(config.max_energy);
+    }
+}
+
+pub fn neutral_resistance(
+    mut nodes_q: Query<&mut GameNode>,
+    time: Res<Time>,
+    graph_res: Res<ComputerGraph>,
+    mut flow_map: ResMut<FlowMap>,
+    config: Res<GameConfig>,
+    game_state: Res<GameState>,
+    paused: Res<Paused>,
+    mut captures: MessageWriter<CaptureEvent>,
+    snapshot: Res<NodeSnapshot>,
+) {
+    const STRONG_NEUTRAL_RATIO: f32 = 0.75;
+
+    if *game_state != GameState::Playing || paused.0 || !config.hostile_neutrals {
+        return;
+    }
+
+    let is_strong_neutral = |idx: NodeIndex| {
+        snapshot.0.get(&idx).is_some_and(|&(owner, hp, max_hp)| {
+            owner == Owner::Neutral && hp >= max_hp * config.neutral_hp_ratio * STRONG_NEUTRAL_RATIO
+        })
+    };
+
+    for mut node in nodes_q.iter_mut() {
+        node.resistance_timer.tick(time.delta());
+        if node.owner == Owner::Neutral || node.resistance_timer.remaining_secs() <= 0.0 {
+            continue;
+        }
+
+        let resisting = graph_res
+            .graph
+            .neighbors(node.index)
+            .filter(|&neighbor| is_strong_neutral(neighbor))
+            .count();
+        if resisting == 0 {
+            continue;
+        }
+
+        node.hp -= config.resistance_decay_rate * resisting as f32 * time.delta_secs();
+        if node.hp > 0.0 {
+            continue;
+        }
+
+        let previous_owner = node.owner;
+        node.begin_color_transition();
+        node.owner = Owner::Neutral;
+        node.hp = config.capture_hp.resolve(node.max_hp);
+        node.targets.clear();
+        if previous_owner == Owner::PLAYER {
+            flow_map.flows.remove(&node.index);
+        }
+        captures.write(CaptureEvent {
+            node: node.index,
+            previous_owner,
+            new_owner: Owner::Neutral,
+        });
+    }
+}
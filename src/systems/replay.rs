@@ -0,0 +1,211 @@
+use bevy::{
+    ecs::{
+        change_detection::DetectChanges,
+        query::Added,
+        system::{Commands, Query, Res, ResMut},
+    },
+    input::{ButtonInput, keyboard::KeyCode},
+    math::Vec3,
+    platform::collections::HashSet,
+    time::Time,
+};
+use petgraph::graph::NodeIndex;
+
+use crate::{
+    components::{GameNode, Packet},
+    resources::{
+        Action, ComputerGraph, EventLog, FlowMap, GameConfig, GameEvent, GameState, GraphEntityMap,
+        KeyBindings, NodePositions, PacketAssets, PacketPool, Paused, ReplayMode,
+    },
+    systems::{
+        map::MatchReset,
+        packet::{edge_length, spawn_packet},
+    },
+};
+
+pub fn not_replaying(replay: Res<ReplayMode>) -> bool {
+    !replay.active
+}
+
+pub fn record_events(
+    mut event_log: ResMut<EventLog>,
+    time: Res<Time>,
+    game_state: Res<GameState>,
+    paused: Res<Paused>,
+    replay: Res<ReplayMode>,
+    flow_map: Res<FlowMap>,
+    nodes_q: Query<&GameNode>,
+    new_packets_q: Query<&Packet, Added<Packet>>,
+) {
+    if replay.active || *game_state != GameState::Playing {
+        return;
+    }
+
+    if !paused.0 {
+        event_log.clock += time.delta_secs();
+    }
+
+    let log = &mut *event_log;
+    let now = log.clock;
+
+    for node in nodes_q.iter() {
+        if let Some(previous) = log.known_owners.insert(node.index, node.owner)
+            && previous != node.owner
+        {
+            log.events.push(GameEvent::Capture {
+                time: now,
+                node: node.index,
+                owner: node.owner,
+            });
+        }
+    }
+
+    if flow_map.is_changed() {
+        let current: HashSet<(NodeIndex, NodeIndex)> = flow_map
+            .flows
+            .iter()
+            .flat_map(|(&from, targets)| targets.iter().map(move |&to| (from, to)))
+            .collect();
+
+        for &(from, to) in current.difference(&log.known_flows) {
+            log.events.push(GameEvent::FlowAdded {
+                time: now,
+                from,
+                to,
+            });
+        }
+        for &(from, to) in log.known_flows.difference(&current) {
+            log.events.push(GameEvent::FlowRemoved {
+                time: now,
+                from,
+                to,
+            });
+        }
+
+        log.known_flows = current;
+    }
+
+    for packet in new_packets_q.iter() {
+        log.events.push(GameEvent::PacketSpawned {
+            time: now,
+            from: packet.from,
+            to: packet.to,
+            owner: packet.owner,
+        });
+    }
+}
+
+pub fn start_replay(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    event_log: Res<EventLog>,
+    graph_res: Res<ComputerGraph>,
+    mut replay: ResMut<ReplayMode>,
+    mut match_reset: MatchReset,
+) {
+    if !bindings.just_pressed(&keyboard, Action::StartReplay) {
+        return;
+    }
+
+    let mut events = if replay.active {
+        replay.events.clone()
+    } else {
+        event_log.events.clone()
+    };
+
+    if events.is_empty() {
+        println!("Nothing to replay yet");
+        return;
+    }
+
+    events.sort_by(|a, b| a.time().total_cmp(&b.time()));
+    println!("Replaying {} recorded event(s)", events.len());
+
+    *replay = ReplayMode {
+        active: true,
+        events,
+        cursor: 0,
+        clock: 0.0,
+    };
+    match_reset.reset(graph_res.clone());
+}
+
+pub fn replay_events(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut replay: ResMut<ReplayMode>,
+    mut nodes_q: Query<&mut GameNode>,
+    mut flow_map: ResMut<FlowMap>,
+    mut packet_pool: ResMut<PacketPool>,
+    entity_map: Res<GraphEntityMap>,
+    graph_res: Res<ComputerGraph>,
+    positions: Res<NodePositions>,
+    packet_assets: Res<PacketAssets>,
+    config: Res<GameConfig>,
+    paused: Res<Paused>,
+    bindings: Res<KeyBindings>,
+) {
+    if !replay.active || paused.0 || replay.cursor >= replay.events.len() {
+        return;
+    }
+
+    replay.clock += time.delta_secs();
+
+    while let Some(&event) = replay.events.get(replay.cursor) {
+        if event.time() > replay.clock {
+            break;
+        }
+        replay.cursor += 1;
+
+        match event {
+            GameEvent::Capture { node, owner, .. } => {
+                if let Some(&entity) = entity_map.nodes.get(&node)
+                    && let Ok(mut game_node) = nodes_q.get_mut(entity)
+                {
+                    game_node.begin_color_transition();
+                    game_node.owner = owner;
+                    game_node.hp = config.capture_hp.resolve(game_node.max_hp);
+                    game_node.targets.clear();
+                    game_node.target_timers.clear();
+                }
+            }
+            GameEvent::FlowAdded { from, to, .. } => {
+                flow_map.insert_flow(from, to);
+            }
+            GameEvent::FlowRemoved { from, to, .. } => {
+                if let Some(targets) = flow_map.flows.get_mut(&from) {
+                    targets.remove(&to);
+                    if targets.is_empty() {
+                        flow_map.flows.remove(&from);
+                    }
+                }
+            }
+            GameEvent::PacketSpawned {
+                from, to, owner, ..
+            } => {
+                let pos = positions.0[&from];
+                spawn_packet(
+                    &mut commands,
+                    &mut packet_pool,
+                    &packet_assets,
+                    Vec3::new(pos.x, pos.y, 1.0),
+                    Packet::new(
+                        from,
+                        to,
+                        owner,
+                        graph_res.graph[from].kind,
+                        edge_length(&graph_res, &positions, from, to),
+                        &config,
+                    ),
+                );
+            }
+        }
+    }
+
+    if replay.cursor >= replay.events.len() {
+        println!(
+            "Replay finished, press {} to start a new match",
+            bindings.key_label(Action::Restart)
+        );
+    }
+}
@@ -0,0 +1,79 @@
+use bevy::{
+    ecs::{
+        message::MessageWriter,
+        system::{Query, Res, ResMut},
+    },
+    input::{ButtonInput, keyboard::KeyCode, mouse::MouseButton},
+};
+
+use crate::{
+    components::{GameNode, Owner},
+    events::CaptureEvent,
+    resources::{
+        Action, FlowMap, GameConfig, GraphEntityMap, InteractionState, KeyBindings, SandboxMode,
+    },
+};
+
+pub fn toggle_sandbox(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    mut sandbox: ResMut<SandboxMode>,
+) {
+    if bindings.just_pressed(&keyboard, Action::ToggleSandbox) {
+        sandbox.0 = !sandbox.0;
+        println!("Sandbox mode: {}", if sandbox.0 { "on" } else { "off" });
+    }
+}
+
+pub fn cycle_node_owner(
+    mouse_buttons: Res<ButtonInput<MouseButton>>,
+    bindings: Res<KeyBindings>,
+    sandbox: Res<SandboxMode>,
+    state: Res<InteractionState>,
+    entity_map: Res<GraphEntityMap>,
+    mut nodes_q: Query<&mut GameNode>,
+    mut flow_map: ResMut<FlowMap>,
+    config: Res<GameConfig>,
+    mut captures: MessageWriter<CaptureEvent>,
+) {
+    if !sandbox.0 || !bindings.mouse_just_pressed(&mouse_buttons, Action::Select) {
+        return;
+    }
+    let Some(mut node) = state
+        .hovered_node
+        .and_then(|idx| entity_map.nodes.get(&idx))
+        .and_then(|&entity| nodes_q.get_mut(entity).ok())
+    else {
+        return;
+    };
+
+    let previous_owner = node.owner;
+    node.begin_color_transition();
+    node.owner = match previous_owner {
+        Owner::Neutral => Owner::Faction(0),
+        Owner::Faction(faction) if faction + 1 < config.faction_count => {
+            Owner::Faction(faction + 1)
+        }
+        Owner::Faction(_) => Owner::Neutral,
+    };
+    node.hp = node.max_hp;
+    node.targets.clear();
+    node.target_timers.clear();
+    if previous_owner == Owner::PLAYER {
+        flow_map.flows.remove(&node.index);
+    }
+    if node.owner == Owner::PLAYER {
+        flow_map.advance(node.index);
+    }
+
+    println!(
+        "Sandbox: node {} is now {}",
+        node.index.index(),
+        node.owner.name()
+    );
+    captures.write(CaptureEvent {
+        node: node.index,
+        previous_owner,
+        new_owner: node.owner,
+    });
+}
@@ -0,0 +1,12 @@
+use bevy::ecs::system::{Query, ResMut};
+
+use crate::{components::GameNode, resources::NodeSnapshot};
+
+pub fn update_node_snapshot(nodes_q: Query<&GameNode>, mut snapshot: ResMut<NodeSnapshot>) {
+    snapshot.0.clear();
+    snapshot.0.extend(
+        nodes_q
+            .iter()
+            .map(|node| (node.index, (node.owner, node.hp, node.max_hp))),
+    );
+}
@@ -0,0 +1,149 @@
+use bevy::{
+    camera::visibility::Visibility,
+    color::Color,
+    ecs::{
+        query::With,
+        system::{Commands, Query, Res},
+    },
+    text::{TextColor, TextFont},
+    ui::{BackgroundColor, GlobalZIndex, Node, PositionType, UiRect, Val, widget::Text},
+    window::{PrimaryWindow, Window},
+};
+
+use crate::{
+    components::{GameNode, Owner, Tooltip},
+    resources::{
+        ComputerGraph, EdgeLoad, FlowMap, FogOfWar, GameConfig, GraphEntityMap, InteractionState,
+    },
+};
+
+const CURSOR_OFFSET: f32 = 16.0;
+
+pub fn setup_tooltip(mut commands: Commands) {
+    commands.spawn((
+        Text::default(),
+        TextFont {
+            font_size: 14.0,
+            ..TextFont::default()
+        },
+        TextColor(Color::srgb(0.9, 0.9, 0.9)),
+        Node {
+            position_type: PositionType::Absolute,
+            padding: UiRect::all(Val::Px(4.0)),
+            ..Node::default()
+        },
+        BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.7)),
+        GlobalZIndex(50),
+        Visibility::Hidden,
+        Tooltip,
+    ));
+}
+
+pub fn update_tooltip(
+    window_q: Query<&Window, With<PrimaryWindow>>,
+    mut tooltip_q: Query<(&mut Text, &mut Node, &mut Visibility), With<Tooltip>>,
+    nodes_q: Query<&GameNode>,
+    interaction: Res<InteractionState>,
+    entity_map: Res<GraphEntityMap>,
+    fog: Res<FogOfWar>,
+    flow_map: Res<FlowMap>,
+    config: Res<GameConfig>,
+    graph_res: Res<ComputerGraph>,
+    edge_load: Res<EdgeLoad>,
+) {
+    let Ok((mut text, mut node, mut visibility)) = tooltip_q.single_mut() else {
+        return;
+    };
+
+    let Some(cursor_pos) = window_q.single().ok().and_then(|w| w.cursor_position()) else {
+        *visibility = Visibility::Hidden;
+        return;
+    };
+    let hovered = interaction
+        .hovered_node
+        .and_then(|idx| entity_map.nodes.get(&idx))
+        .and_then(|&entity| nodes_q.get(entity).ok());
+
+    if let Some(game_node) = hovered {
+        text.0 = node_info(game_node, &nodes_q, &entity_map, &fog, &flow_map, &config);
+    } else if let Some(edge_idx) = interaction.hovered_edge
+        && let Some((u, v)) = graph_res.graph.edge_endpoints(edge_idx)
+    {
+        text.0 = format!(
+            "Edge {} - {}\nLength {:.2}",
+            u.index(),
+            v.index(),
+            graph_res.graph[edge_idx].length
+        );
+        if fog.is_visible(u) && fog.is_visible(v) {
+            let load = edge_load.0.get(&edge_idx).copied().unwrap_or(0);
+            text.0 += &format!("\nPackets {load}");
+        }
+    } else {
+        *visibility = Visibility::Hidden;
+        return;
+    }
+
+    node.left = Val::Px(cursor_pos.x + CURSOR_OFFSET);
+    node.top = Val::Px(cursor_pos.y + CURSOR_OFFSET);
+    *visibility = Visibility::Inherited;
+}
+
+fn node_info(
+    game_node: &GameNode,
+    nodes_q: &Query<&GameNode>,
+    entity_map: &GraphEntityMap,
+    fog: &FogOfWar,
+    flow_map: &FlowMap,
+    config: &GameConfig,
+) -> String {
+    if fog.is_visible(game_node.index) {
+        let mut info = format!(
+            "Node {}\n{}\nHP {:.0}/{:.0}",
+            game_node.index.index(),
+            game_node.owner.name(),
+            game_node.hp,
+            game_node.max_hp
+        );
+        if game_node.owner != Owner::PLAYER {
+            let damage_rate =
+                incoming_damage_rate(game_node, nodes_q, entity_map, flow_map, config);
+            if damage_rate > 0.0 {
+                info += &format!("\nCapture in ~{:.0}s", game_node.hp / damage_rate);
+            }
+        }
+        info
+    } else {
+        format!("Node {}\nUnknown", game_node.index.index())
+    }
+}
+
+fn incoming_damage_rate(
+    target: &GameNode,
+    nodes_q: &Query<&GameNode>,
+    entity_map: &GraphEntityMap,
+    flow_map: &FlowMap,
+    config: &GameConfig,
+) -> f32 {
+    let sustained_rate = config.energy_regen_rate / config.packet_energy_cost;
+
+    flow_map
+        .flows
+        .iter()
+        .filter(|(_, targets)| targets.contains(&target.index))
+        .filter_map(|(source, targets)| {
+            let node = entity_map
+                .nodes
+                .get(source)
+                .and_then(|&entity| nodes_q.get(entity).ok())
+                .filter(|node| node.owner == Owner::PLAYER)?;
+            let total_weight: f32 = targets.iter().map(|&t| flow_map.weight(*source, t)).sum();
+            let share = flow_map.weight(*source, target.index) / total_weight;
+            let fire_rate = (node.firing_multiplier() / config.spawn_interval).min(sustained_rate);
+            let power = config.packet_power
+                * node.kind.packet_power_multiplier()
+                * target.kind.damage_multiplier();
+            Some(share * fire_rate * power)
+        })
+        .sum()
+}
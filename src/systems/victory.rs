@@ -0,0 +1,163 @@
+use bevy::{
+    color::Color,
+    ecs::{
+        change_detection::DetectChanges,
+        entity::Entity,
+        query::With,
+        spawn::SpawnRelated,
+        system::{Commands, Query, Res, ResMut},
+    },
+    platform::collections::{HashMap, HashSet},
+    prelude::children,
+    text::{TextColor, TextFont},
+    time::Time,
+    ui::{AlignItems, FlexDirection, GlobalZIndex, JustifyContent, Node, Val, widget::Text},
+};
+
+use crate::{
+    components::{GameNode, GameOverUi, Owner},
+    resources::{Action, GameState, KeyBindings, MatchClock, Paused, VictoryMode},
+};
+
+pub fn check_victory(
+    nodes_q: Query<&GameNode>,
+    mut game_state: ResMut<GameState>,
+    mut clock: ResMut<MatchClock>,
+    victory_mode: Res<VictoryMode>,
+    time: Res<Time>,
+    paused: Res<Paused>,
+) {
+    if *game_state != GameState::Playing || paused.0 {
+        return;
+    }
+
+    let factions: HashSet<Owner> = nodes_q
+        .iter()
+        .map(|node| node.owner)
+        .filter(|&owner| owner != Owner::Neutral)
+        .collect();
+
+    if !factions.contains(&Owner::PLAYER) {
+        *game_state = GameState::PlayerLost;
+        println!("Defeat: the enemy captured all of your nodes");
+        return;
+    } else if factions.len() == 1 {
+        *game_state = GameState::PlayerWon;
+        println!("Victory: all enemy nodes captured");
+        return;
+    }
+
+    clock.elapsed += time.delta_secs();
+
+    let mut counts: HashMap<Owner, usize> = HashMap::new();
+    for node in nodes_q.iter().filter(|node| node.owner != Owner::Neutral) {
+        *counts.entry(node.owner).or_default() += 1;
+    }
+    let total = nodes_q.iter().count();
+    let leader = counts
+        .iter()
+        .max_by_key(|&(&owner, &count)| (count, owner == Owner::PLAYER))
+        .map(|(&owner, &count)| (owner, count));
+
+    let winner = match *victory_mode {
+        VictoryMode::Annihilation => None,
+        VictoryMode::Majority { share, hold_secs } => {
+            let majority_owner = leader
+                .filter(|&(_, count)| count as f32 > total as f32 * share)
+                .map(|(owner, _)| owner);
+            clock.majority = match (majority_owner, clock.majority) {
+                (Some(owner), Some((held_by, held))) if owner == held_by => {
+                    Some((owner, held + time.delta_secs()))
+                }
+                (Some(owner), _) => Some((owner, 0.0)),
+                (None, _) => None,
+            };
+            clock
+                .majority
+                .filter(|&(_, held)| held >= hold_secs)
+                .map(|(owner, _)| owner)
+        }
+        VictoryMode::Timed { duration_secs } => {
+            let tied = leader.is_some_and(|(owner, count)| {
+                counts
+                    .iter()
+                    .any(|(&other, &other_count)| other != owner && other_count == count)
+            });
+            (clock.elapsed >= duration_secs).then_some(match leader {
+                Some((owner, _)) if !tied => owner,
+                _ => Owner::Neutral,
+            })
+        }
+    };
+
+    match winner {
+        Some(Owner::PLAYER) => {
+            *game_state = GameState::PlayerWon;
+            println!("Victory: you held the most nodes");
+        }
+        Some(Owner::Neutral) => {
+            *game_state = GameState::PlayerLost;
+            println!("Defeat: time ran out without a clear leader");
+        }
+        Some(_) => {
+            *game_state = GameState::PlayerLost;
+            println!("Defeat: the enemy held the most nodes");
+        }
+        None => {}
+    }
+}
+
+pub fn show_game_over_ui(
+    mut commands: Commands,
+    game_state: Res<GameState>,
+    bindings: Res<KeyBindings>,
+    overlay_q: Query<Entity, With<GameOverUi>>,
+) {
+    if !game_state.is_changed() {
+        return;
+    }
+
+    for entity in overlay_q.iter() {
+        commands.entity(entity).despawn();
+    }
+
+    let (label, color) = match *game_state {
+        GameState::Playing => return,
+        GameState::PlayerWon => ("VICTORY", Color::srgb(0.0, 4.0, 5.0)),
+        GameState::PlayerLost => ("DEFEAT", Color::srgb(5.0, 1.0, 1.0)),
+    };
+
+    commands.spawn((
+        Node {
+            width: Val::Percent(100.0),
+            height: Val::Percent(100.0),
+            flex_direction: FlexDirection::Column,
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            ..Node::default()
+        },
+        GlobalZIndex(100),
+        GameOverUi,
+        children![
+            (
+                Text::new(label),
+                TextFont {
+                    font_size: 96.0,
+                    ..TextFont::default()
+                },
+                TextColor(color),
+            ),
+            (
+                Text::new(format!(
+                    "Press {} to restart",
+                    bindings.key_label(Action::Restart)
+                )),
+                TextFont {
+                    font_size: 24.0,
+                    ..TextFont::default()
+                },
+                TextColor(Color::srgb(0.8, 0.8, 0.8)),
+            )
+        ],
+    ));
+}
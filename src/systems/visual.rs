@@ -2,54 +2,126 @@ use bevy::{
     asset::Assets,
     color::{Alpha, Color, LinearRgba, Mix},
     ecs::{
-        query::Without,
+        change_detection::DetectChanges,
+        query::{With, Without},
         system::{Query, Res, ResMut},
     },
     gizmos::gizmos::Gizmos,
     input::{ButtonInput, keyboard::KeyCode},
+    math::{Rect, Vec3},
+    platform::collections::HashMap,
     sprite_render::{ColorMaterial, MeshMaterial2d},
+    time::Time,
     transform::components::Transform,
 };
+use petgraph::graph::NodeIndex;
 
 use crate::{
-    NODE_MAX_HP,
-    components::{GameNode, Packet},
-    resources::{ComputerGraph, FlowMap, GraphEntityMap, InteractionState},
+    components::{GameEdge, GameNode, Owner, Packet},
+    resources::{
+        Action, ComputerGraph, EdgeLoad, EdgeMaterials, FlowMap, FogOfWar, GameConfig,
+        GraphEntityMap, InteractionState, KeyBindings, NodePositions, PacketAssets, Palette,
+        ShowEdgeLoad, ShowFrontier,
+    },
+    systems::fog::visible_nodes,
 };
 
+const FIRE_FLASH_SCALE: f32 = 0.12;
+const FIRE_FLASH_GLOW: f32 = 0.6;
+
 pub fn update_visuals(
-    nodes_q: Query<(&GameNode, &MeshMaterial2d<ColorMaterial>)>,
-    mut edges_q: Query<&mut MeshMaterial2d<ColorMaterial>, (Without<GameNode>, Without<Packet>)>,
+    mut nodes_q: Query<(
+        &mut GameNode,
+        &MeshMaterial2d<ColorMaterial>,
+        &mut Transform,
+    )>,
+    mut edges_q: Query<&mut MeshMaterial2d<ColorMaterial>, (With<GameEdge>, Without<GameNode>)>,
+    mut packets_q: Query<
+        (&Packet, &mut MeshMaterial2d<ColorMaterial>),
+        (Without<GameEdge>, Without<GameNode>),
+    >,
+    packet_assets: Res<PacketAssets>,
+    mut fog: ResMut<FogOfWar>,
     mut materials: ResMut<Assets<ColorMaterial>>,
     interaction: Res<InteractionState>,
     graph_res: Res<ComputerGraph>,
     entity_map: Res<GraphEntityMap>,
     flow_map: Res<FlowMap>,
     keyboard: Res<ButtonInput<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    mut edge_materials: ResMut<EdgeMaterials>,
+    show_frontier: Res<ShowFrontier>,
+    time: Res<Time>,
+    palette: Res<Palette>,
 ) {
-    let color_default_edge = materials.add(Color::srgb(0.2, 0.2, 0.2));
-    let color_flow_edge = materials.add(Color::srgb(0.0, 2.5, 10.0));
+    if palette.is_changed() {
+        packet_assets.recolor(&mut materials, *palette);
+        edge_materials.recolor(&mut materials, *palette);
+    }
+
+    if fog.enabled {
+        let owned = nodes_q
+            .iter()
+            .filter(|(node, ..)| node.owner == Owner::PLAYER)
+            .map(|(node, ..)| node.index);
+        fog.visible = visible_nodes(&graph_res, owned, fog.radius);
+    }
 
-    let is_erasing = keyboard.pressed(KeyCode::ShiftLeft) || keyboard.pressed(KeyCode::ShiftRight);
-    let path_color_value = if is_erasing {
-        Color::srgb(10.0, 0.0, 0.0)
+    let is_erasing = bindings.pressed(&keyboard, Action::Erase);
+    let color_path_edge = if is_erasing {
+        edge_materials.erase.clone()
     } else {
-        Color::srgb(10.0, 10.0, 0.0)
+        edge_materials.path.clone()
     };
-    let color_path_edge = materials.add(path_color_value);
 
     for mut mat in edges_q.iter_mut() {
-        mat.0 = color_default_edge.clone();
+        mat.0 = edge_materials.default.clone();
+    }
+
+    if show_frontier.0 {
+        let owners: HashMap<NodeIndex, Owner> = nodes_q
+            .iter()
+            .map(|(node, ..)| (node.index, node.owner))
+            .collect();
+
+        for (&edge_idx, &entity) in &entity_map.edges {
+            let Some((u, v)) = graph_res.graph.edge_endpoints(edge_idx) else {
+                continue;
+            };
+            let (Some(&owner_u), Some(&owner_v)) = (owners.get(&u), owners.get(&v)) else {
+                continue;
+            };
+
+            if owner_u != owner_v
+                && owner_u != Owner::Neutral
+                && owner_v != Owner::Neutral
+                && fog.is_visible(u)
+                && fog.is_visible(v)
+                && let Ok(mut mat) = edges_q.get_mut(entity)
+            {
+                mat.0 = edge_materials.frontier.clone();
+            }
+        }
+    }
+
+    if let Some(edge_idx) = interaction.hovered_edge
+        && let Some(&entity) = entity_map.edges.get(&edge_idx)
+        && let Ok(mut mat) = edges_q.get_mut(entity)
+    {
+        mat.0 = edge_materials.hover.clone();
     }
 
     for (source, targets) in &flow_map.flows {
         for &target in targets {
-            if let Some(edge_idx) = graph_res.0.find_edge(*source, target) {
-                if let Some(&entity) = entity_map.edges.get(&edge_idx) {
-                    if let Ok(mut mat) = edges_q.get_mut(entity) {
-                        mat.0 = color_flow_edge.clone();
-                    }
+            if let Some(edge_idx) = graph_res.graph.find_edge(*source, target)
+                && let Some(&entity) = entity_map.edges.get(&edge_idx)
+                && let Ok(mut mat) = edges_q.get_mut(entity)
+            {
+                let mut brightness = flow_map.weight(*source, target);
+                if interaction.hovered_flow == Some((*source, target)) {
+                    brightness *= 1.5;
                 }
+                mat.0 = edge_materials.flow(&mut materials, brightness);
             }
         }
     }
@@ -58,34 +130,62 @@ pub fn update_visuals(
         for window in interaction.path.windows(2) {
             let u = window[0];
             let v = window[1];
-            if let Some(edge_idx) = graph_res.0.find_edge(u, v) {
-                if let Some(&entity) = entity_map.edges.get(&edge_idx) {
-                    if let Ok(mut mat) = edges_q.get_mut(entity) {
-                        mat.0 = color_path_edge.clone();
-                    }
-                }
+            if let Some(edge_idx) = graph_res.graph.find_edge(u, v)
+                && let Some(&entity) = entity_map.edges.get(&edge_idx)
+                && let Ok(mut mat) = edges_q.get_mut(entity)
+            {
+                mat.0 = color_path_edge.clone();
             }
         }
     }
 
-    for (node, mat_handle) in nodes_q.iter() {
+    for (mut node, mat_handle, mut transform) in nodes_q.iter_mut() {
+        let mut owner_color = node.owner.color(*palette);
+        if let Some((from_owner, timer)) = &mut node.color_transition {
+            timer.tick(time.delta());
+            owner_color = from_owner
+                .color(*palette)
+                .mix(&owner_color, timer.fraction());
+            if timer.is_finished() {
+                node.color_transition = None;
+            }
+        }
+
+        let mut flash = 0.0;
+        if let Some(timer) = &mut node.fire_flash {
+            timer.tick(time.delta());
+            flash = 1.0 - timer.fraction();
+            if timer.is_finished() {
+                node.fire_flash = None;
+            }
+        }
+
         if let Some(material) = materials.get_mut(mat_handle) {
-            let mut base_color = node.owner.color();
+            if !fog.is_visible(node.index) {
+                material.color = FogOfWar::HIDDEN_COLOR;
+                transform.scale = Vec3::ONE;
+                continue;
+            }
 
-            if Some(node.index) == interaction.selected_source {
-                base_color = Color::srgb(1.0, 5.0, 1.0);
+            transform.scale = Vec3::splat(node.visual_scale() * (1.0 + FIRE_FLASH_SCALE * flash));
+
+            let mut base_color = owner_color;
+
+            if Some(node.index) == interaction.dragging_from
+                || interaction.selected_sources.contains(&node.index)
+            {
+                base_color = palette.selected_tint();
             } else if interaction.path.contains(&node.index) {
-                let tint = if is_erasing {
-                    Color::srgb(1.0, 0.0, 0.0)
-                } else {
-                    Color::srgb(1.0, 1.0, 0.0)
-                };
-                base_color = base_color.mix(&tint, 0.6);
+                base_color = base_color.mix(&palette.path_tint(is_erasing), 0.6);
+            } else if interaction.unreachable && Some(node.index) == interaction.hovered_node {
+                let flash = 0.5 + 0.5 * (time.elapsed_secs() * 12.0).sin();
+                base_color = base_color.mix(&palette.path_tint(true), 0.4 + 0.5 * flash);
             } else if Some(node.index) == interaction.hovered_node {
-                base_color = base_color.mix(&Color::srgb(1.0, 1.0, 0.0), 0.3);
+                let selectable = node.owner == Owner::PLAYER;
+                base_color = base_color.mix(&palette.path_tint(!selectable), 0.3);
             }
 
-            let hp_factor = 0.3 + 0.7 * (node.hp / NODE_MAX_HP);
+            let hp_factor = (0.3 + 0.7 * (node.hp / node.max_hp)) * (1.0 + FIRE_FLASH_GLOW * flash);
             let final_color = LinearRgba::from(base_color);
 
             material.color = Color::LinearRgba(LinearRgba {
@@ -96,11 +196,142 @@ pub fn update_visuals(
             });
         }
     }
+    for (packet, mut mat) in packets_q.iter_mut() {
+        mat.0 = if fog.is_visible(packet.from) || fog.is_visible(packet.to) {
+            packet_assets.material(packet.owner)
+        } else {
+            packet_assets.hidden_material.clone()
+        };
+    }
+}
+
+pub fn toggle_frontier(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    mut show: ResMut<ShowFrontier>,
+) {
+    if bindings.just_pressed(&keyboard, Action::ToggleFrontier) {
+        show.0 = !show.0;
+    }
 }
 
-pub fn draw_packet_trails(mut gizmos: Gizmos, packets: Query<(&Transform, &Packet)>) {
+pub fn toggle_edge_load(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    mut show: ResMut<ShowEdgeLoad>,
+) {
+    if bindings.just_pressed(&keyboard, Action::ToggleEdgeLoad) {
+        show.0 = !show.0;
+    }
+}
+
+pub fn update_edge_widths(
+    mut edges_q: Query<&mut Transform, With<GameEdge>>,
+    entity_map: Res<GraphEntityMap>,
+    graph_res: Res<ComputerGraph>,
+    edge_load: Res<EdgeLoad>,
+    fog: Res<FogOfWar>,
+    show: Res<ShowEdgeLoad>,
+    time: Res<Time>,
+) {
+    const WIDTH_PER_PACKET: f32 = 0.25;
+    const MAX_WIDTH: f32 = 4.0;
+    const WIDTH_SMOOTHING: f32 = 8.0;
+
+    let blend = 1.0 - (-WIDTH_SMOOTHING * time.delta_secs()).exp();
+    for (&edge_idx, &entity) in &entity_map.edges {
+        let Ok(mut transform) = edges_q.get_mut(entity) else {
+            continue;
+        };
+        let visible = graph_res
+            .graph
+            .edge_endpoints(edge_idx)
+            .is_some_and(|(u, v)| fog.is_visible(u) && fog.is_visible(v));
+        let load = match edge_load.0.get(&edge_idx) {
+            Some(&load) if show.0 && visible => load,
+            _ => 0,
+        };
+
+        let width = (1.0 + load as f32 * WIDTH_PER_PACKET).min(MAX_WIDTH);
+        transform.scale.y += (width - transform.scale.y) * blend;
+    }
+}
+
+pub fn cycle_palette(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    mut palette: ResMut<Palette>,
+) {
+    if bindings.just_pressed(&keyboard, Action::CyclePalette) {
+        *palette = palette.next();
+        println!("Palette: {}", palette.name());
+    }
+}
+
+pub fn draw_flow_direction(
+    mut gizmos: Gizmos,
+    time: Res<Time>,
+    flow_map: Res<FlowMap>,
+    positions: Res<NodePositions>,
+    config: Res<GameConfig>,
+) {
+    const CHEVRON_SPACING: f32 = 0.08;
+    const CHEVRON_SIZE: f32 = 0.015;
+    const LANE_OFFSET: f32 = 0.012;
+    const NODE_CLEARANCE: f32 = 0.07;
+
+    let color = Color::srgb(0.0, 2.5, 10.0);
+    let phase = (time.elapsed_secs() * config.packet_travel_speed()).rem_euclid(CHEVRON_SPACING);
+
+    for (source, targets) in &flow_map.flows {
+        for target in targets {
+            let (Some(&start), Some(&end)) = (positions.0.get(source), positions.0.get(target))
+            else {
+                continue;
+            };
+
+            let len = start.distance(end);
+            let dir = (end - start).normalize_or_zero();
+            let side = dir.perp();
+            let lane = start + side * LANE_OFFSET;
+
+            let mut dist = NODE_CLEARANCE + phase;
+            while dist < len - NODE_CLEARANCE {
+                let tip = lane + dir * dist;
+                let back = tip - dir * CHEVRON_SIZE;
+                gizmos.line_2d(back + side * CHEVRON_SIZE, tip, color);
+                gizmos.line_2d(back - side * CHEVRON_SIZE, tip, color);
+                dist += CHEVRON_SPACING;
+            }
+        }
+    }
+}
+
+pub fn draw_selection_box(
+    mut gizmos: Gizmos,
+    interaction: Res<InteractionState>,
+    palette: Res<Palette>,
+) {
+    if let Some((start, end)) = interaction.selection_box {
+        let area = Rect::from_corners(start, end);
+        gizmos.rect_2d(area.center(), area.size(), palette.selected_tint());
+    }
+}
+
+pub fn draw_packet_trails(
+    mut gizmos: Gizmos,
+    packets: Query<(&Transform, &Packet)>,
+    config: Res<GameConfig>,
+    palette: Res<Palette>,
+    fog: Res<FogOfWar>,
+) {
     for (transform, packet) in packets.iter() {
         let start = transform.translation.truncate();
-        gizmos.circle_2d(start, 0.03, packet.owner.color().with_alpha(0.5));
+        let color = if fog.is_visible(packet.from) || fog.is_visible(packet.to) {
+            packet.owner.color(*palette)
+        } else {
+            FogOfWar::HIDDEN_COLOR
+        };
+        gizmos.circle_2d(start, config.packet_radius * 2.0, color.with_alpha(0.5));
     }
 }